@@ -0,0 +1,548 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Variable de entorno con la ruta del fichero de claves de API
+pub const API_KEYS_FILE_ENV: &str = "API_KEYS_FILE";
+
+/// Variable de entorno con la ruta del fichero JWKS para validar tokens JWT
+pub const JWKS_FILE_ENV: &str = "JWKS_FILE";
+
+/// Variable de entorno con el emisor (`iss`) exigido a los tokens JWT
+pub const JWT_ISSUER_ENV: &str = "JWT_ISSUER";
+
+/// Variable de entorno con la audiencia (`aud`) exigida a los tokens JWT
+pub const JWT_AUDIENCE_ENV: &str = "JWT_AUDIENCE";
+
+/// Cabecera alternativa a `Authorization: Bearer` para enviar una clave de API
+const CABECERA_API_KEY: &str = "x-api-key";
+
+// =================== PERMISOS ===================
+
+/// Permiso (scope) concedido a un cliente
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permiso {
+    /// Herramientas que evalúan un caso o un periodo
+    Evaluar,
+    /// Evaluación de lotes con `evaluar_lote`
+    Lote,
+    /// Recarga de los modelos de decisión con `POST /admin/recargar`
+    Recargar,
+}
+
+impl Permiso {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permiso::Evaluar => "evaluar",
+            Permiso::Lote => "lote",
+            Permiso::Recargar => "recargar",
+        }
+    }
+
+    /// Permiso de un scope de un token JWT; los scopes de otras aplicaciones se ignoran
+    pub fn from_scope(scope: &str) -> Option<Self> {
+        match scope {
+            "evaluar" => Some(Permiso::Evaluar),
+            "lote" => Some(Permiso::Lote),
+            "recargar" => Some(Permiso::Recargar),
+            _ => None,
+        }
+    }
+
+    /// Permiso necesario para llamar a una herramienta
+    pub fn de_herramienta(herramienta: &str) -> Self {
+        match herramienta {
+            "evaluar_lote" => Permiso::Lote,
+            _ => Permiso::Evaluar,
+        }
+    }
+}
+
+impl fmt::Display for Permiso {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Cliente autenticado y permisos que tiene concedidos
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identidad {
+    /// Nombre de la clave de API o `sub` del token JWT
+    pub nombre: String,
+    pub permisos: BTreeSet<Permiso>,
+}
+
+impl Identidad {
+    /// Identidad de las peticiones cuando la autenticación está desactivada: puede hacerlo todo
+    pub fn anonima() -> Self {
+        Self {
+            nombre: "anonimo".to_string(),
+            permisos: [Permiso::Evaluar, Permiso::Lote, Permiso::Recargar].into(),
+        }
+    }
+
+    pub fn permite(&self, permiso: Permiso) -> bool {
+        self.permisos.contains(&permiso)
+    }
+}
+
+// =================== ERRORES ===================
+
+#[derive(Debug)]
+pub enum AutenticacionError {
+    Lectura { path: PathBuf, source: std::io::Error },
+    Formato { path: PathBuf, message: String },
+    SinCredenciales,
+    ClaveNoValida,
+    TokenNoValido(String),
+    SinPermiso { nombre: String, permiso: Permiso },
+}
+
+impl fmt::Display for AutenticacionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutenticacionError::Lectura { path, source } => {
+                write!(f, "No se puede leer el fichero de autenticación '{}': {}", path.display(), source)
+            },
+            AutenticacionError::Formato { path, message } => {
+                write!(f, "El fichero de autenticación '{}' no es válido: {}", path.display(), message)
+            },
+            AutenticacionError::SinCredenciales => {
+                write!(f, "Falta la cabecera Authorization: Bearer <token> o {}", CABECERA_API_KEY)
+            },
+            AutenticacionError::ClaveNoValida => write!(f, "La clave de API no es válida"),
+            AutenticacionError::TokenNoValido(motivo) => write!(f, "El token no es válido: {}", motivo),
+            AutenticacionError::SinPermiso { nombre, permiso } => {
+                write!(f, "El cliente '{}' no tiene el permiso '{}'", nombre, permiso)
+            },
+        }
+    }
+}
+
+impl std::error::Error for AutenticacionError {}
+
+impl AutenticacionError {
+    fn status(&self) -> StatusCode {
+        match self {
+            AutenticacionError::SinPermiso { .. } => StatusCode::FORBIDDEN,
+            AutenticacionError::Lectura { .. } | AutenticacionError::Formato { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+impl IntoResponse for AutenticacionError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let mut response = (status, Json(serde_json::json!({ "error": self.to_string() }))).into_response();
+        if status == StatusCode::UNAUTHORIZED {
+            response.headers_mut().insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+}
+
+// =================== CONFIGURACIÓN ===================
+
+/// Entrada del fichero de claves de API:
+/// `[{"nombre": "rrhh", "clave": "...", "permisos": ["evaluar", "lote"]}]`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClaveApi {
+    pub nombre: String,
+    pub clave: String,
+    pub permisos: BTreeSet<Permiso>,
+}
+
+/// Validación de tokens JWT firmados con alguna de las claves de un JWKS
+#[derive(Debug)]
+struct ValidadorJwt {
+    jwks: JwkSet,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+/// Claims de un token JWT que se usan para construir la identidad
+#[derive(Debug, Deserialize)]
+struct Claims {
+    #[serde(default)]
+    sub: Option<String>,
+    /// `scope` (RFC 8693, separado por espacios) o `scp` (lista)
+    #[serde(default, alias = "scp")]
+    scope: Option<Scopes>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Scopes {
+    Texto(String),
+    Lista(Vec<String>),
+}
+
+impl Scopes {
+    fn permisos(&self) -> BTreeSet<Permiso> {
+        match self {
+            Scopes::Texto(texto) => texto.split_whitespace().filter_map(Permiso::from_scope).collect(),
+            Scopes::Lista(lista) => lista.iter().filter_map(|scope| Permiso::from_scope(scope)).collect(),
+        }
+    }
+}
+
+/// Autenticación de los servidores HTTP. Sin claves de API ni JWKS está desactivada
+/// y todas las peticiones se atienden como [`Identidad::anonima`]
+#[derive(Debug, Clone, Default)]
+pub struct Autenticacion {
+    /// Identidad por SHA-256 de la clave, para no comparar las claves en claro
+    claves: Arc<HashMap<[u8; 32], Identidad>>,
+    jwt: Option<Arc<ValidadorJwt>>,
+}
+
+impl Autenticacion {
+    pub fn activa(&self) -> bool {
+        !self.claves.is_empty() || self.jwt.is_some()
+    }
+
+    pub fn con_claves_api(mut self, claves: impl IntoIterator<Item = ClaveApi>) -> Self {
+        let claves = claves
+            .into_iter()
+            .map(|clave| (Sha256::digest(clave.clave.as_bytes()).into(), Identidad { nombre: clave.nombre, permisos: clave.permisos }))
+            .collect();
+        self.claves = Arc::new(claves);
+        self
+    }
+
+    pub fn con_jwks(mut self, jwks: JwkSet, issuer: Option<String>, audience: Option<String>) -> Self {
+        self.jwt = Some(Arc::new(ValidadorJwt { jwks, issuer, audience }));
+        self
+    }
+
+    /// Identidad del cliente a partir de las cabeceras `Authorization: Bearer` o `x-api-key`
+    pub fn verificar_cabeceras(&self, headers: &HeaderMap) -> Result<Identidad, AutenticacionError> {
+        if !self.activa() {
+            return Ok(Identidad::anonima());
+        }
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|valor| valor.to_str().ok())
+            .and_then(|valor| valor.strip_prefix("Bearer "));
+        let api_key = headers.get(CABECERA_API_KEY).and_then(|valor| valor.to_str().ok());
+        match (bearer, api_key) {
+            (Some(token), _) => self.verificar(token.trim()),
+            (None, Some(clave)) => self.verificar_clave(clave.trim()),
+            (None, None) => Err(AutenticacionError::SinCredenciales),
+        }
+    }
+
+    /// Identidad de un token Bearer: una clave de API o, si hay JWKS, un token JWT
+    pub fn verificar(&self, token: &str) -> Result<Identidad, AutenticacionError> {
+        match (self.verificar_clave(token), &self.jwt) {
+            (Ok(identidad), _) => Ok(identidad),
+            (Err(_), Some(jwt)) => jwt.verificar(token),
+            (Err(e), None) => Err(e),
+        }
+    }
+
+    fn verificar_clave(&self, clave: &str) -> Result<Identidad, AutenticacionError> {
+        let hash: [u8; 32] = Sha256::digest(clave.as_bytes()).into();
+        self.claves.get(&hash).cloned().ok_or(AutenticacionError::ClaveNoValida)
+    }
+}
+
+impl ValidadorJwt {
+    fn verificar(&self, token: &str) -> Result<Identidad, AutenticacionError> {
+        let no_valido = |e: jsonwebtoken::errors::Error| AutenticacionError::TokenNoValido(e.to_string());
+        let cabecera = jsonwebtoken::decode_header(token).map_err(no_valido)?;
+
+        // Con `kid` se usa esa clave; sin él se prueban todas las del JWKS
+        let candidatas: Vec<_> = match &cabecera.kid {
+            Some(kid) => self.jwks.find(kid).into_iter().collect(),
+            None => self.jwks.keys.iter().collect(),
+        };
+        if candidatas.is_empty() {
+            return Err(AutenticacionError::TokenNoValido(format!("no hay ninguna clave en el JWKS con kid {:?}", cabecera.kid)));
+        }
+
+        let mut ultimo_error = None;
+        for jwk in candidatas {
+            // El algoritmo lo fija la clave del JWKS; el `alg` de la cabecera solo tiene que coincidir
+            let Some(algoritmo) = algoritmo_de_clave(jwk) else {
+                ultimo_error = Some(format!("la clave {:?} del JWKS no declara un algoritmo de firma (alg)", jwk.common.key_id));
+                continue;
+            };
+            if algoritmo != cabecera.alg {
+                ultimo_error = Some(format!("el token está firmado con {:?} y la clave {:?} del JWKS con {:?}", cabecera.alg, jwk.common.key_id, algoritmo));
+                continue;
+            }
+
+            let mut validation = Validation::new(algoritmo);
+            validation.validate_aud = self.audience.is_some();
+            if let Some(audience) = &self.audience {
+                validation.set_audience(&[audience]);
+            }
+            if let Some(issuer) = &self.issuer {
+                validation.set_issuer(&[issuer]);
+            }
+
+            let resultado = DecodingKey::from_jwk(jwk)
+                .and_then(|clave| jsonwebtoken::decode::<Claims>(token, &clave, &validation));
+            match resultado {
+                Ok(datos) => {
+                    return Ok(Identidad {
+                        nombre: datos.claims.sub.unwrap_or_else(|| "jwt".to_string()),
+                        permisos: datos.claims.scope.map(|scopes| scopes.permisos()).unwrap_or_default(),
+                    });
+                },
+                Err(e) => ultimo_error = Some(e.to_string()),
+            }
+        }
+        Err(AutenticacionError::TokenNoValido(ultimo_error.expect("hay al menos una clave candidata")))
+    }
+}
+
+/// Algoritmo de firma de una clave del JWKS: el de su `alg` o, si no lo declara, el único que
+/// admite su curva (EdDSA para Ed25519, ES256 para P-256 y ES384 para P-384). Las claves RSA sin
+/// `alg`, habituales en los JWKS de los proveedores de identidad, se usan con RS256, el algoritmo
+/// por defecto de OpenID Connect. Las claves HMAC sin `alg` no se usan
+fn algoritmo_de_clave(jwk: &Jwk) -> Option<Algorithm> {
+    match (&jwk.common.key_algorithm, &jwk.algorithm) {
+        (Some(key_algorithm), _) => key_algorithm.to_string().parse().ok(),
+        (None, AlgorithmParameters::OctetKeyPair(params)) if params.curve == EllipticCurve::Ed25519 => Some(Algorithm::EdDSA),
+        (None, AlgorithmParameters::EllipticCurve(params)) => match params.curve {
+            EllipticCurve::P256 => Some(Algorithm::ES256),
+            EllipticCurve::P384 => Some(Algorithm::ES384),
+            _ => None,
+        },
+        (None, AlgorithmParameters::RSA(_)) => Some(Algorithm::RS256),
+        (None, _) => None,
+    }
+}
+
+// =================== ARGUMENTOS CLI ===================
+
+/// Autenticación de los servidores HTTP. Es la sección `[auth]` de la configuración
+#[derive(Debug, Clone, Default, PartialEq, clap::Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthArgs {
+    /// Fichero JSON con las claves de API y sus permisos: [{"nombre", "clave", "permisos": ["evaluar", "lote", "recargar"]}]
+    #[arg(long = "api-keys-file", env = API_KEYS_FILE_ENV, value_name = "PATH")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_keys_file: Option<PathBuf>,
+
+    /// Fichero JWKS con las claves públicas para validar tokens JWT. Cada clave fija su algoritmo con `alg`;
+    /// sin él, las RSA se usan con RS256. Los permisos se toman del claim `scope` o `scp`
+    #[arg(long = "jwks-file", env = JWKS_FILE_ENV, value_name = "PATH")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks_file: Option<PathBuf>,
+
+    /// Emisor (`iss`) exigido a los tokens JWT
+    #[arg(long = "jwt-issuer", env = JWT_ISSUER_ENV, value_name = "ISS")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwt_issuer: Option<String>,
+
+    /// Audiencia (`aud`) exigida a los tokens JWT
+    #[arg(long = "jwt-audience", env = JWT_AUDIENCE_ENV, value_name = "AUD")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwt_audience: Option<String>,
+}
+
+impl AuthArgs {
+    /// Carga los ficheros de claves indicados. Sin ninguno la autenticación queda desactivada
+    pub fn load(&self) -> Result<Autenticacion, AutenticacionError> {
+        let mut autenticacion = Autenticacion::default();
+        if let Some(path) = &self.api_keys_file {
+            let claves: Vec<ClaveApi> = leer_json(path)?;
+            tracing::info!("{} claves de API cargadas desde {}", claves.len(), path.display());
+            autenticacion = autenticacion.con_claves_api(claves);
+        }
+        if let Some(path) = &self.jwks_file {
+            let jwks: JwkSet = leer_json(path)?;
+            tracing::info!("{} claves JWKS cargadas desde {}", jwks.keys.len(), path.display());
+            autenticacion = autenticacion.con_jwks(jwks, self.jwt_issuer.clone(), self.jwt_audience.clone());
+        }
+        if !autenticacion.activa() {
+            tracing::warn!("Autenticación desactivada: se aceptan conexiones anónimas con todos los permisos");
+        }
+        Ok(autenticacion)
+    }
+}
+
+fn leer_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, AutenticacionError> {
+    let contenido = std::fs::read_to_string(path)
+        .map_err(|source| AutenticacionError::Lectura { path: path.to_path_buf(), source })?;
+    serde_json::from_str(&contenido)
+        .map_err(|e| AutenticacionError::Formato { path: path.to_path_buf(), message: e.to_string() })
+}
+
+// =================== MIDDLEWARE ===================
+
+/// Exige credenciales válidas en todas las rutas de `router` y deja la [`Identidad`]
+/// del cliente en las extensiones de la petición. rmcp copia las partes de la petición
+/// HTTP en el contexto de cada mensaje MCP, así `Calculadora` comprueba los permisos por herramienta
+pub fn proteger(router: Router, autenticacion: Autenticacion) -> Router {
+    router.layer(middleware::from_fn_with_state(autenticacion, autenticar))
+}
+
+async fn autenticar(State(autenticacion): State<Autenticacion>, mut request: Request, next: Next) -> Response {
+    match autenticacion.verificar_cabeceras(request.headers()) {
+        Ok(identidad) => {
+            request.extensions_mut().insert(identidad);
+            next.run(request).await
+        },
+        Err(e) => {
+            tracing::warn!("Petición rechazada en {}: {}", request.uri().path(), e);
+            e.into_response()
+        },
+    }
+}
+
+/// Comprueba que la identidad tenga el permiso
+pub fn exigir(identidad: &Identidad, permiso: Permiso) -> Result<(), AutenticacionError> {
+    if identidad.permite(permiso) {
+        Ok(())
+    } else {
+        Err(AutenticacionError::SinPermiso { nombre: identidad.nombre.clone(), permiso })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+    use jsonwebtoken::{EncodingKey, Header, jwk::KeyAlgorithm};
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    /// Par de claves Ed25519 generado para el test y su JWKS público
+    fn clave_firma(kid: &str) -> (EncodingKey, JwkSet) {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        let par = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let jwks = serde_json::from_value(serde_json::json!({
+            "keys": [{"kty": "OKP", "crv": "Ed25519", "kid": kid, "x": URL_SAFE_NO_PAD.encode(par.public_key().as_ref())}]
+        })).unwrap();
+        (EncodingKey::from_ed_der(pkcs8.as_ref()), jwks)
+    }
+
+    fn token(clave: &EncodingKey, kid: &str, claims: serde_json::Value) -> String {
+        let mut cabecera = Header::new(Algorithm::EdDSA);
+        cabecera.kid = Some(kid.to_string());
+        jsonwebtoken::encode(&cabecera, &claims, clave).unwrap()
+    }
+
+    fn dentro_de(segundos: i64) -> i64 {
+        chrono::Utc::now().timestamp() + segundos
+    }
+
+    #[test]
+    fn test_claves_api_con_permisos() {
+        let autenticacion = Autenticacion::default().con_claves_api([
+            ClaveApi { nombre: "portal".to_string(), clave: "clave-portal".to_string(), permisos: [Permiso::Evaluar].into() },
+            ClaveApi { nombre: "rrhh".to_string(), clave: "clave-rrhh".to_string(), permisos: [Permiso::Evaluar, Permiso::Lote].into() },
+        ]);
+
+        let mut headers = HeaderMap::new();
+        assert!(matches!(autenticacion.verificar_cabeceras(&headers), Err(AutenticacionError::SinCredenciales)));
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer clave-portal"));
+        let portal = autenticacion.verificar_cabeceras(&headers).unwrap();
+        assert_eq!(portal.nombre, "portal");
+        assert!(exigir(&portal, Permiso::de_herramienta("evaluar_supuesto_excedencia")).is_ok());
+        assert!(matches!(exigir(&portal, Permiso::de_herramienta("evaluar_lote")), Err(AutenticacionError::SinPermiso { .. })));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CABECERA_API_KEY, HeaderValue::from_static("clave-rrhh"));
+        let rrhh = autenticacion.verificar_cabeceras(&headers).unwrap();
+        assert!(rrhh.permite(Permiso::Lote));
+        assert!(!rrhh.permite(Permiso::Recargar));
+
+        assert!(matches!(autenticacion.verificar("otra-clave"), Err(AutenticacionError::ClaveNoValida)));
+        assert_eq!(Autenticacion::default().verificar_cabeceras(&HeaderMap::new()).unwrap(), Identidad::anonima());
+    }
+
+    #[test]
+    fn test_jwt_firmado_con_clave_del_jwks() {
+        let (clave, jwks) = clave_firma("clave-1");
+        let autenticacion = Autenticacion::default()
+            .con_jwks(jwks, Some("https://idp.example".to_string()), Some("bon-calculadora".to_string()));
+
+        let valido = token(&clave, "clave-1", serde_json::json!({
+            "sub": "gestor", "iss": "https://idp.example", "aud": "bon-calculadora",
+            "exp": dentro_de(300), "scope": "evaluar recargar openid"
+        }));
+        let identidad = autenticacion.verificar(&valido).unwrap();
+        assert_eq!(identidad.nombre, "gestor");
+        assert_eq!(identidad.permisos, [Permiso::Evaluar, Permiso::Recargar].into());
+
+        let con_scp = token(&clave, "clave-1", serde_json::json!({
+            "iss": "https://idp.example", "aud": "bon-calculadora", "exp": dentro_de(300), "scp": ["lote"]
+        }));
+        assert_eq!(autenticacion.verificar(&con_scp).unwrap().permisos, [Permiso::Lote].into());
+
+        let caducado = token(&clave, "clave-1", serde_json::json!({
+            "iss": "https://idp.example", "aud": "bon-calculadora", "exp": dentro_de(-3600), "scope": "evaluar"
+        }));
+        assert!(matches!(autenticacion.verificar(&caducado), Err(AutenticacionError::TokenNoValido(_))));
+
+        let otra_audiencia = token(&clave, "clave-1", serde_json::json!({
+            "iss": "https://idp.example", "aud": "otra-api", "exp": dentro_de(300), "scope": "evaluar"
+        }));
+        assert!(autenticacion.verificar(&otra_audiencia).is_err());
+
+        // Firmado con una clave que no está en el JWKS, aunque reutilice su kid
+        let (otra_clave, _) = clave_firma("clave-1");
+        let falsificado = token(&otra_clave, "clave-1", serde_json::json!({
+            "iss": "https://idp.example", "aud": "bon-calculadora", "exp": dentro_de(300), "scope": "evaluar"
+        }));
+        assert!(matches!(autenticacion.verificar(&falsificado), Err(AutenticacionError::TokenNoValido(_))));
+    }
+
+    #[test]
+    fn test_jwt_con_el_algoritmo_de_la_clave() {
+        let (clave, mut jwks) = clave_firma("clave-1");
+        let claims = serde_json::json!({ "exp": dentro_de(300), "scope": "evaluar" });
+
+        jwks.keys[0].common.key_algorithm = Some(KeyAlgorithm::EdDSA);
+        let autenticacion = Autenticacion::default().con_jwks(jwks.clone(), None, None);
+        assert!(autenticacion.verificar(&token(&clave, "clave-1", claims.clone())).is_ok());
+
+        // Si la clave declara otro algoritmo, la cabecera del token no puede imponer el suyo
+        jwks.keys[0].common.key_algorithm = Some(KeyAlgorithm::ES256);
+        let autenticacion = Autenticacion::default().con_jwks(jwks, None, None);
+        let Err(AutenticacionError::TokenNoValido(motivo)) = autenticacion.verificar(&token(&clave, "clave-1", claims)) else {
+            panic!("se esperaba un token no válido");
+        };
+        assert!(motivo.contains("EdDSA"), "{}", motivo);
+
+        // Una clave RSA sin `alg` se usa con RS256, y un token firmado con otro algoritmo RSA se rechaza
+        let rsa: Jwk = serde_json::from_value(serde_json::json!({ "kty": "RSA", "kid": "rsa", "n": "AQAB", "e": "AQAB" })).unwrap();
+        assert_eq!(algoritmo_de_clave(&rsa), Some(Algorithm::RS256));
+        let autenticacion = Autenticacion::default().con_jwks(JwkSet { keys: vec![rsa] }, None, None);
+        let ps256 = format!(
+            "{}.{}.firma",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"PS256","typ":"JWT","kid":"rsa"}"#),
+            URL_SAFE_NO_PAD.encode(r#"{"scope":"evaluar"}"#),
+        );
+        let Err(AutenticacionError::TokenNoValido(motivo)) = autenticacion.verificar(&ps256) else {
+            panic!("se esperaba un token no válido");
+        };
+        assert!(motivo.contains("RS256"), "{}", motivo);
+
+        let rsa: Jwk = serde_json::from_value(serde_json::json!({ "kty": "RSA", "kid": "rsa", "alg": "RS512", "n": "AQAB", "e": "AQAB" })).unwrap();
+        assert_eq!(algoritmo_de_clave(&rsa), Some(Algorithm::RS512));
+        let hmac: Jwk = serde_json::from_value(serde_json::json!({ "kty": "oct", "kid": "hmac", "k": "c2VjcmV0" })).unwrap();
+        assert_eq!(algoritmo_de_clave(&hmac), None);
+    }
+}
@@ -1,57 +1,149 @@
+use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize, Deserializer, de::Error as DeError};
-use zen_engine::DecisionEngine;
-use zen_engine::model::DecisionContent;
-use zen_engine::{EvaluationError, NodeError};
+use zen_engine::{DecisionGraphResponse, EvaluationError, EvaluationOptions, NodeError};
 use std::fmt;
+use std::sync::Arc;
+
+use super::autenticacion::{Identidad, Permiso, exigir};
+use super::decisiones::{DecisionRegistry, HerramientaDecision};
+use super::esquema::apply_model_enums;
+use super::instrucciones::{descripcion_evaluar, descripcion_supuestos_aplicables, instrucciones};
+use super::metricas::{GuardaSesion, Metricas};
+use super::lote::{EvaluarLoteParams, MAX_CASOS_LOTE, ResultadoCaso, ResultadoLote, ResumenLote};
+use super::prompts;
+use super::recursos::{self, SIN_SUPUESTO};
+use super::periodo::{
+    ImporteMes, ImportePeriodo, ImportePeriodoParams, MAX_ANOS_PERIODO, TramoEjercicio,
+    fin_duracion_maxima, prorratear, tramos_mensuales,
+};
+use super::modelo::{CompiledDecision, DecisionModel, ModelRegistry};
+use super::traza::{NODO_SUPUESTOS, ReglaAplicada, TrazaEvaluacion};
+use super::valores::{Parentesco, Situacion, ValorNoValido};
 
 use rmcp::{
     ServerHandler,
-    handler::server::{router::tool::ToolRouter, tool::Parameters},
-    model::{ServerCapabilities, ServerInfo, CallToolResult, Content},
-    ErrorData as McpError,
-    schemars, tool, tool_handler, tool_router,
+    handler::server::{router::tool::ToolRouter, tool::{Parameters, ToolCallContext, cached_schema_for_type}},
+    model::{
+        ServerCapabilities, ServerInfo, CallToolRequestParam, CallToolResult, Content,
+        ListToolsResult, PaginatedRequestParam, Tool, ListResourcesResult, JsonObject,
+        ListResourceTemplatesResult, ReadResourceRequestParam, ReadResourceResult,
+        ListPromptsResult, GetPromptRequestParam, GetPromptResult,
+    },
+    service::{NotificationContext, RequestContext},
+    ErrorData as McpError, RoleServer,
+    schemars, tool, tool_router,
 };
 
 // =================== ESTRUCTURAS DE ERROR ===================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
 pub struct ValidationError {
     pub message: String,
+    /// Puntero JSON al campo erróneo, p. ej. `/input/parentesco`
     pub path: String,
+    /// Valor recibido en ese campo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    /// Valores admitidos por el schema de entrada, si el campo es un `enum`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_values: Option<Vec<serde_json::Value>>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Campo '{}': {}", self.path, self.message)?;
+        if let Some(allowed_values) = &self.allowed_values {
+            let allowed: Vec<String> = allowed_values.iter().map(|v| v.to_string()).collect();
+            write!(f, ". Valores permitidos: {}", allowed.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl From<jsonschema::ValidationError<'_>> for ValidationError {
+    fn from(error: jsonschema::ValidationError<'_>) -> Self {
+        let allowed_values = match &error.kind {
+            jsonschema::error::ValidationErrorKind::Enum { options } => options.as_array().cloned(),
+            _ => None,
+        };
+        Self {
+            message: error.to_string(),
+            path: error.instance_path.to_string(),
+            value: Some(error.instance.clone().into_owned()),
+            allowed_values,
+        }
+    }
+}
+
+impl From<ValorNoValido> for ValidationError {
+    fn from(error: ValorNoValido) -> Self {
+        Self {
+            message: format!("'{}' no es un valor admitido de {}", error.valor, error.campo),
+            path: format!("/input/{}", error.campo),
+            value: Some(serde_json::Value::String(error.valor)),
+            allowed_values: Some(error.permitidos.iter().map(|v| serde_json::json!(v)).collect()),
+        }
+    }
+}
+
+impl ValidationError {
+    /// Errores de `parentesco` y `situacion` que no corresponden a ningún valor ni alias. Estos
+    /// valores fallan al deserializar los parámetros, antes de validar con el schema del modelo,
+    /// así que se comprueban aparte para informarlos y contarlos como el resto
+    pub fn de_valores(arguments: &JsonObject) -> Vec<ValidationError> {
+        let texto = |campo: &str| arguments.get(campo).and_then(serde_json::Value::as_str);
+        let mut errores = Vec::new();
+        if let Some(Err(error)) = texto("parentesco").map(str::parse::<Parentesco>) {
+            errores.push(error.into());
+        }
+        if let Some(Err(error)) = texto("situacion").map(str::parse::<Situacion>) {
+            errores.push(error.into());
+        }
+        errores
+    }
 }
 
+/// Errores tal y como los devuelve el validador de zen-engine: `{"errors": [...]}`
 #[derive(Debug, Deserialize)]
 pub struct ValidationErrorSource {
     pub errors: Vec<ValidationError>,
 }
 
+/// Error de nodo serializado por zen-engine: `{"type": "Validation", "source": {"errors": [...]}}`
 #[derive(Debug, Deserialize)]
 pub struct ValidationErrorDetails {
     pub source: ValidationErrorSource,
     #[serde(rename = "type")]
-    #[allow(dead_code)]
     pub error_type: String,
 }
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum ExcedenciaError {
     ValidationError(Vec<ValidationError>),
     ZenEngineError(EvaluationError),
     SerializationError(serde_json::Error),
+    EjercicioNoDisponibleError { ejercicio: i32, disponibles: Vec<i32> },
+    TrazaNoDisponibleError,
 }
 
 impl fmt::Display for ExcedenciaError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ExcedenciaError::ValidationError(errors) => {
-                write!(f, "Errores de validación:\n")?;
+                writeln!(f, "Errores de validación:")?;
                 for error in errors {
-                    write!(f, "  - {}: {}\n", error.path, error.message)?;
+                    writeln!(f, "  - {}", error)?;
                 }
                 Ok(())
             },
             ExcedenciaError::ZenEngineError(e) => write!(f, "Error del motor de decisión: {}", e),
             ExcedenciaError::SerializationError(e) => write!(f, "Error de serialización: {}", e),
+            ExcedenciaError::EjercicioNoDisponibleError { ejercicio, disponibles } => {
+                let disponibles: Vec<String> = disponibles.iter().map(|e| e.to_string()).collect();
+                write!(f, "No hay modelo de decisión para el ejercicio {}. Ejercicios disponibles: {}", ejercicio, disponibles.join(", "))
+            },
+            ExcedenciaError::TrazaNoDisponibleError => write!(f, "El motor de decisión no ha devuelto la traza de la evaluación"),
         }
     }
 }
@@ -118,6 +210,17 @@ where
     deserializer.deserialize_any(BoolOrStringVisitor)
 }
 
+/// Deserializa un valor opcional que puede ser bool, string ("true"/"false") o null
+fn deserialize_option_bool_or_string<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        Some(value) => deserialize_bool_or_string(value).map(Some).map_err(DeError::custom),
+        None => Ok(None),
+    }
+}
+
 /// Deserializa un valor que puede ser f64 o string numérico
 fn deserialize_f64_or_string<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
 where
@@ -172,6 +275,18 @@ where
             self.visit_str(&value)
         }
 
+        fn visit_map<A>(self, map: A) -> Result<Option<f64>, A::Error>
+        where
+            A: serde::de::MapAccess<'de>,
+        {
+            // Con `arbitrary_precision` (activado por zen-engine) serde_json entrega
+            // los números leídos de texto como un mapa con el literal
+            let number = serde_json::Number::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+            number.as_f64()
+                .map(Some)
+                .ok_or_else(|| DeError::custom(format!("invalid number: {}", number)))
+        }
+
         fn visit_none<E>(self) -> Result<Option<f64>, E>
         where
             E: DeError,
@@ -190,44 +305,157 @@ where
     deserializer.deserialize_any(F64OrStringVisitor)
 }
 
+/// Deserializa un año que puede ser entero o string numérico
+pub fn deserialize_year_or_string<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match deserialize_f64_or_string(deserializer)? {
+        Some(value) if value.fract() == 0.0 && (1900.0..=9999.0).contains(&value) => Ok(Some(value as i32)),
+        Some(value) => Err(DeError::custom(format!("invalid year: {}", value))),
+        None => Ok(None),
+    }
+}
+
 // =================== ESTRUCTURAS DE DATOS ===================
 
 // Direct parameters structure for MCP (flattened)
 #[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
 pub struct ExcedenciaDirectParams {
-    #[schemars(description = "Relación familiar con la persona que necesita cuidado. VALORES VÁLIDOS: 'padre', 'madre', 'hijo', 'hija', 'conyuge', 'pareja', 'esposo', 'esposa', 'mujer', 'marido'. Ejemplo: 'madre'")]
-    pub parentesco: String,
+    #[schemars(description = "Relación familiar con la persona que necesita cuidado. VALORES VÁLIDOS: 'padre', 'madre', 'hijo', 'hija', 'conyuge', 'pareja', 'esposo', 'esposa', 'mujer', 'marido'. Se admiten mayúsculas, tildes y sinónimos ('Cónyuge', 'pareja de hecho'). Ejemplo: 'madre'")]
+    pub parentesco: Parentesco,
     
-    #[schemars(description = "Situación que motiva la necesidad de cuidado. VALORES VÁLIDOS: 'parto', 'adopcion', 'acogimiento', 'parto_multiple', 'adopcion_multiple', 'acogimiento_multiple', 'enfermedad', 'accidente'. Ejemplo: 'parto'")]
-    pub situacion: String,
+    #[schemars(description = "Situación que motiva la necesidad de cuidado. VALORES VÁLIDOS: 'parto', 'adopcion', 'acogimiento', 'parto_multiple', 'adopcion_multiple', 'acogimiento_multiple', 'enfermedad', 'accidente'. Se admiten mayúsculas, tildes y sinónimos ('Adopción', 'nacimiento' = 'parto'). Ejemplo: 'parto'")]
+    pub situacion: Situacion,
     
     #[schemars(description = "¿Es una familia monoparental? Acepta valores booleanos (true/false) o strings ('true'/'false'). Use exactamente: true (para familias monoparentales) o false (para familias con ambos padres). Ejemplo: true")]
     #[serde(deserialize_with = "deserialize_bool_or_string")]
     pub familia_monoparental: bool,
     
     #[schemars(description = "Número total de hijos incluyendo al recién nacido (requerido para Supuesto B - tercer hijo o más). Acepta números (3) o strings ('3'). Use números enteros. Ejemplo: 3")]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[serde(deserialize_with = "deserialize_f64_or_string")]
     pub numero_hijos: Option<f64>,
+
+    #[schemars(description = "Hijos, incluido el recién nacido, con su fecha de nacimiento y grado de discapacidad (requerido para comprobar las edades del Supuesto B: al menos 2 menores de 6 años, o de 9 con discapacidad mayor del 33%, en la fecha de nacimiento del último hijo). Si se omite numero_hijos se toma el número de elementos. Ejemplo: [{\"fecha_nacimiento\": \"2025-03-01\"}, {\"fecha_nacimiento\": \"2021-06-15\"}, {\"fecha_nacimiento\": \"2017-01-10\", \"grado_discapacidad\": 40}]")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hijos: Option<Vec<Hijo>>,
+
+    #[schemars(description = "¿La persona que necesita cuidado ha sido hospitalizada? (requerido para Supuesto A). Acepta true/false o 'true'/'false'. Si se omite y no se indica fecha_hospitalizacion, el Supuesto A queda pendiente de documentación. Ejemplo: true")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_option_bool_or_string")]
+    pub hospitalizacion: Option<bool>,
+
+    #[schemars(description = "Fecha de la hospitalización (AAAA-MM-DD) (Supuesto A). Si se indica, se entiende que ha habido hospitalización. Ejemplo: '2025-02-20'")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fecha_hospitalizacion: Option<NaiveDate>,
+
+    #[schemars(description = "¿La persona necesita cuidado continuado? (requerido para Supuesto A). Acepta true/false o 'true'/'false'. Si se omite, el Supuesto A queda pendiente de documentación. Ejemplo: true")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_option_bool_or_string")]
+    pub cuidado_continuado: Option<bool>,
+
+    #[schemars(description = "Duración prevista del acogimiento en meses (Supuesto C: el acogimiento ha de durar más de un año). Acepta números (18) o strings ('18'). Alternativa a fecha_inicio_acogimiento y fecha_fin_acogimiento. Ejemplo: 18")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_f64_or_string")]
+    pub duracion_acogimiento_meses: Option<f64>,
+
+    #[schemars(description = "Fecha de inicio del acogimiento (AAAA-MM-DD) (Supuesto C). Ejemplo: '2025-01-15'")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fecha_inicio_acogimiento: Option<NaiveDate>,
+
+    #[schemars(description = "Fecha de fin prevista del acogimiento (AAAA-MM-DD) (Supuesto C). Ejemplo: '2026-07-15'")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fecha_fin_acogimiento: Option<NaiveDate>,
+
+    #[schemars(description = "Ejercicio (año) de la normativa a aplicar, normalmente el año en que comenzó la excedencia. Opcional: si se omite se aplica la normativa vigente en la fecha de la consulta. Ejemplo: 2025")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_year_or_string")]
+    pub ejercicio: Option<i32>,
+}
+
+impl ExcedenciaDirectParams {
+    /// Convierte los parámetros planos de la herramienta en la petición anidada del motor
+    pub fn into_request(self) -> (ExcedenciaRequest, Option<i32>) {
+        let request = ExcedenciaRequest {
+            input: ExcedenciaInput {
+                parentesco: self.parentesco,
+                situacion: self.situacion,
+                familia_monoparental: self.familia_monoparental,
+                numero_hijos: self.numero_hijos.or_else(|| self.hijos.as_ref().map(|hijos| hijos.len() as f64)),
+                hijos: self.hijos,
+                hospitalizacion: self.hospitalizacion,
+                fecha_hospitalizacion: self.fecha_hospitalizacion,
+                cuidado_continuado: self.cuidado_continuado,
+                duracion_acogimiento_meses: self.duracion_acogimiento_meses,
+                fecha_inicio_acogimiento: self.fecha_inicio_acogimiento,
+                fecha_fin_acogimiento: self.fecha_fin_acogimiento,
+            }
+        };
+        (request, self.ejercicio)
+    }
 }
 
 // Internal structure for the ZEN engine (nested)
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
 pub struct ExcedenciaInput {
     #[schemars(description = "Es un string que indica relación familiar con la persona que necesita cuidado. Valores válidos: padre, madre, hijo, hija, conyuge, pareja, esposo, esposa, mujer, marido")]
-    pub parentesco: String,
+    pub parentesco: Parentesco,
     
     #[schemars(description = "Es un string que indica la situación que motiva la necesidad de cuidado. Valores válidos: parto, adopcion, acogimiento, parto_multiple, adopcion_multiple, acogimiento_multiple, enfermedad, accidente")]
-    pub situacion: String,
+    pub situacion: Situacion,
     
     #[schemars(description = "Es un booleano para indicar si la familia es monoparental. Acepta valores booleanos (true/false) o strings ('true'/'false'). Valores válidos: true, false, 'true', 'false'")]
     #[serde(deserialize_with = "deserialize_bool_or_string")]
     pub familia_monoparental: bool,
     
     #[schemars(description = "Es un número que indica el número de hijos incluyendo al recién nacido si es el caso. Acepta números (4) o strings ('4'). Se expresa sin comillas. Valores válidos: número")]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[serde(deserialize_with = "deserialize_f64_or_string")]
     pub numero_hijos: Option<f64>,
+
+    #[schemars(description = "Es una lista con los hijos, incluido el recién nacido, con su fecha de nacimiento y grado de discapacidad. Se usa para comprobar las edades del Supuesto B")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hijos: Option<Vec<Hijo>>,
+
+    #[schemars(description = "Es un booleano que indica si la persona que necesita cuidado ha sido hospitalizada (Supuesto A)")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_option_bool_or_string")]
+    pub hospitalizacion: Option<bool>,
+
+    #[schemars(description = "Es la fecha de la hospitalización (AAAA-MM-DD) (Supuesto A)")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fecha_hospitalizacion: Option<NaiveDate>,
+
+    #[schemars(description = "Es un booleano que indica si la persona necesita cuidado continuado (Supuesto A)")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_option_bool_or_string")]
+    pub cuidado_continuado: Option<bool>,
+
+    #[schemars(description = "Es un número que indica la duración prevista del acogimiento en meses (Supuesto C)")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_f64_or_string")]
+    pub duracion_acogimiento_meses: Option<f64>,
+
+    #[schemars(description = "Es la fecha de inicio del acogimiento (AAAA-MM-DD) (Supuesto C)")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fecha_inicio_acogimiento: Option<NaiveDate>,
+
+    #[schemars(description = "Es la fecha de fin prevista del acogimiento (AAAA-MM-DD) (Supuesto C)")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fecha_fin_acogimiento: Option<NaiveDate>,
+}
+
+/// Hijo de la unidad familiar, para comprobar el requisito de edad del Supuesto B
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct Hijo {
+    #[schemars(description = "Fecha de nacimiento (AAAA-MM-DD). Ejemplo: '2021-06-15'")]
+    pub fecha_nacimiento: NaiveDate,
+
+    #[schemars(description = "Grado de discapacidad reconocido en porcentaje (0-100). Con más del 33% el límite de edad del Supuesto B es 9 años en lugar de 6. Ejemplo: 40")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_f64_or_string")]
+    pub grado_discapacidad: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
@@ -248,16 +476,112 @@ struct ExcedenciaOutput {
     errores: Vec<String>,
     #[serde(default)]
     advertencias: Vec<String>,
+    #[serde(default)]
+    duracion_maxima_meses: Option<u32>,
+    #[serde(default)]
+    estado: Option<EstadoResolucion>,
+}
+
+/// Estado de la resolución de un supuesto
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EstadoResolucion {
+    /// Se cumplen los requisitos que el modelo puede comprobar
+    Reconocido,
+    /// No se cumple algún requisito; el motivo está en `errores`
+    Denegado,
+    /// Faltan datos para acreditar algún requisito; los que faltan están en `errores`
+    PendienteDocumentacion,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
 pub struct ExcedenciaResponse {
     #[schemars(description = "Resultado de la evaluación")]
     pub output: ExcedenciaOutputForSchema,
+    #[schemars(description = "Datos evaluados, con parentesco y situación normalizados a su valor canónico")]
     #[serde(default)]
     pub input: Option<ExcedenciaInput>,
     #[serde(default)]
     pub parentesco_valido: Option<bool>,
+    #[schemars(description = "Modelo de decisión con el que se ha evaluado, para poder reproducir la evaluación")]
+    #[serde(default)]
+    pub modelo: Option<ModeloAplicado>,
+}
+
+/// Modelo de decisión con el que se ha realizado una evaluación
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ModeloAplicado {
+    #[schemars(description = "Ejercicio (año) de la normativa aplicada")]
+    pub ejercicio: i32,
+
+    #[schemars(description = "Huella SHA-256 del modelo de decisión, identifica exactamente las reglas aplicadas")]
+    pub version: String,
+}
+
+impl From<&DecisionModel> for ModeloAplicado {
+    fn from(model: &DecisionModel) -> Self {
+        Self {
+            ejercicio: model.ejercicio,
+            version: model.version.clone(),
+        }
+    }
+}
+
+/// Resultado de `explicar_evaluacion`: la evaluación y la traza del modelo que la justifica
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ExplicacionEvaluacion {
+    #[schemars(description = "Resultado de la evaluación, igual que el de evaluar_supuesto_excedencia")]
+    pub resultado: ExcedenciaResponse,
+
+    #[schemars(description = "Regla aplicada, comprobación de parentesco y nodos evaluados")]
+    pub traza: TrazaEvaluacion,
+}
+
+/// Supuesto cuyas condiciones se cumplen, con su importe y requisitos
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct SupuestoAplicable {
+    #[schemars(description = "Letra del supuesto (A, B, C, D, E)")]
+    pub supuesto: String,
+
+    #[schemars(description = "Descripción del supuesto")]
+    pub descripcion: String,
+
+    #[schemars(description = "Importe mensual de la bonificación en euros")]
+    pub importe_mensual: i32,
+
+    #[schemars(description = "Requisitos adicionales que deben cumplirse para este supuesto")]
+    #[serde(default)]
+    pub requisitos_adicionales: String,
+
+    #[schemars(description = "Estado de la resolución del supuesto; 'pendiente_documentacion' si faltan datos para acreditar algún requisito")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estado: Option<EstadoResolucion>,
+
+    #[schemars(description = "Requisitos no cumplidos o pendientes de acreditar")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errores: Vec<String>,
+
+    #[schemars(description = "Identificador de la regla de la tabla de supuestos que se cumple")]
+    pub regla: String,
+}
+
+/// Resultado de `evaluar_supuestos_aplicables`
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct SupuestosAplicables {
+    #[schemars(description = "Supuestos cuyas condiciones se cumplen, en orden de prelación normativa")]
+    pub supuestos: Vec<SupuestoAplicable>,
+
+    #[schemars(description = "Primer supuesto aplicable en orden de prelación normativa, o null si no aplica ninguno. Si la primera regla que se cumple deniega un supuesto (p. ej. el B sin dos hijos menores de 6 años), evaluar_supuesto_excedencia devuelve ese supuesto denegado y este campo el siguiente aplicable")]
+    pub supuesto_prelacion: Option<String>,
+
+    #[schemars(description = "Supuesto de mayor importe mensual; ante importes iguales, el de mayor prelación")]
+    pub supuesto_mas_favorable: Option<String>,
+
+    #[schemars(description = "Datos evaluados, con parentesco y situación normalizados a su valor canónico")]
+    pub input: ExcedenciaInput,
+
+    #[schemars(description = "Modelo de decisión con el que se ha evaluado")]
+    pub modelo: ModeloAplicado,
 }
 
 // Estructura para el schema JSON (para documentación MCP)
@@ -286,147 +610,395 @@ pub struct ExcedenciaOutputForSchema {
     #[schemars(description = "Lista de advertencias o información adicional relevante")]
     #[serde(default)]
     pub advertencias: Vec<String>,
+
+    #[schemars(description = "Duración máxima del supuesto en meses desde el inicio de la excedencia, si el modelo de decisión la define")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duracion_maxima_meses: Option<u32>,
+
+    #[schemars(description = "Estado de la resolución: 'reconocido', 'denegado' o 'pendiente_documentacion' si faltan datos para acreditar algún requisito (listados en errores)")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estado: Option<EstadoResolucion>,
 }
 
 // =================== MOTOR DE DECISIÓN ===================
 
 #[derive(Debug, Clone)]
-struct ExcedenciaDecisionEngine;
+struct ExcedenciaDecisionEngine {
+    models: ModelRegistry,
+    metricas: Metricas,
+}
 
 impl ExcedenciaDecisionEngine {
-    fn new() -> Self {
-        Self
+    fn new(models: ModelRegistry) -> Self {
+        Self { models, metricas: Metricas::new() }
     }
 
-    async fn evaluate_excedencia(&self, request: &ExcedenciaRequest) -> Result<ExcedenciaResponse, ExcedenciaError> {
-        // Cargar la decisión desde el archivo JSON
-        let decision_content: DecisionContent = 
-            serde_json::from_str(include_str!("ayuda-excedencia-2025.json"))
-            .map_err(ExcedenciaError::from)?;
-        let engine = DecisionEngine::default();
-        let decision = engine.create_decision(decision_content.into());
-        
+    /// Modelo del ejercicio indicado o, si no se indica, el aplicable a fecha de hoy
+    fn resolve_model(&self, ejercicio: Option<i32>) -> Result<Arc<DecisionModel>, ExcedenciaError> {
+        let model = match ejercicio {
+            Some(ejercicio) => self.models.get(ejercicio),
+            None => self.models.current().or_else(|| {
+                self.models.ejercicios().first().and_then(|first| self.models.get(*first))
+            }),
+        };
+        model.ok_or_else(|| ExcedenciaError::EjercicioNoDisponibleError {
+            ejercicio: ejercicio.unwrap_or_else(|| chrono::Local::now().year()),
+            disponibles: self.models.ejercicios(),
+        })
+    }
+
+    async fn evaluate_excedencia(&self, request: &ExcedenciaRequest, ejercicio: Option<i32>) -> Result<ExcedenciaResponse, ExcedenciaError> {
         // Convertir struct a JSON y luego a Variable
         let json_value = serde_json::to_value(request)?;
-        
-        match decision.evaluate(json_value.into()).await {
-            Ok(result) => {
-                // Convertir el resultado de Variable a Value y luego deserializar
-                let result_value: serde_json::Value = result.result.into();
-                let mut response: ExcedenciaResponse = serde_json::from_value(result_value)?;
-                
-                // Convertir ExcedenciaOutput a ExcedenciaOutputForSchema
-                let internal_output: ExcedenciaOutput = serde_json::from_value(
-                    serde_json::to_value(&response.output)?
-                )?;
-                
-                response.output = ExcedenciaOutputForSchema {
-                    descripcion: internal_output.descripcion,
-                    importe_mensual: internal_output.importe_mensual,
-                    requisitos_adicionales: internal_output.requisitos_adicionales,
-                    supuesto: internal_output.supuesto,
-                    tiene_derecho_potencial: internal_output.tiene_derecho_potencial,
-                    errores: internal_output.errores,
-                    advertencias: internal_output.advertencias,
-                };
-                
-                Ok(response)
+        let (mut response, _) = self.evaluate_value(json_value, ejercicio, false).await?;
+
+        // Informar de los valores normalizados que se han evaluado realmente
+        response.input = Some(request.input.clone());
+        Ok(response)
+    }
+
+    /// Evalúa con traza y explica qué regla y qué nodos han llevado al resultado
+    async fn explain_excedencia(&self, request: &ExcedenciaRequest, ejercicio: Option<i32>) -> Result<ExplicacionEvaluacion, ExcedenciaError> {
+        let json_value = serde_json::to_value(request)?;
+        let (mut resultado, traza) = self.evaluate_value(json_value, ejercicio, true).await?;
+        resultado.input = Some(request.input.clone());
+
+        Ok(ExplicacionEvaluacion {
+            resultado,
+            traza: traza.ok_or(ExcedenciaError::TrazaNoDisponibleError)?,
+        })
+    }
+
+    async fn evaluate_value(&self, json_value: serde_json::Value, ejercicio: Option<i32>, trace: bool) -> Result<(ExcedenciaResponse, Option<TrazaEvaluacion>), ExcedenciaError> {
+        // La decisión ya está compilada en el modelo del ejercicio; se toma una copia
+        // para que una recarga en caliente no afecte a la evaluación en curso
+        let model = self.resolve_model(ejercicio)?;
+        self.evaluate_model(&model, json_value, trace).await
+    }
+
+    /// Evalúa con el modelo indicado y registra el resultado y la duración en las métricas
+    async fn evaluate_model(&self, model: &Arc<DecisionModel>, json_value: serde_json::Value, trace: bool) -> Result<(ExcedenciaResponse, Option<TrazaEvaluacion>), ExcedenciaError> {
+        self.evaluate_graph(
+            model,
+            &model.decision,
+            json_value,
+            trace,
+            |result| Self::excedencia_response(model, result, trace),
+            |(response, _)| (response.output.supuesto.as_str(), response.output.tiene_derecho_potencial),
+        ).await
+    }
+
+    /// Camino común de todas las evaluaciones: ejecuta `decision`, convierte su resultado con
+    /// `convertir` o el error de zen-engine con [`Self::evaluation_error`], y registra en las
+    /// métricas el supuesto y el derecho que indica `resultado`, la duración o los errores de validación
+    async fn evaluate_graph<T>(
+        &self,
+        model: &Arc<DecisionModel>,
+        decision: &CompiledDecision,
+        json_value: serde_json::Value,
+        trace: bool,
+        convertir: impl FnOnce(DecisionGraphResponse) -> Result<T, ExcedenciaError>,
+        resultado: impl FnOnce(&T) -> (&str, bool),
+    ) -> Result<T, ExcedenciaError> {
+        let inicio = std::time::Instant::now();
+        let options = EvaluationOptions { trace: Some(trace), max_depth: None };
+        let evaluacion = decision.evaluate_with_opts(json_value.clone().into(), options).await
+            .map_err(|zen_error| Self::evaluation_error(model, &json_value, *zen_error))
+            .and_then(convertir);
+        match &evaluacion {
+            Ok(valor) => {
+                let (supuesto, tiene_derecho_potencial) = resultado(valor);
+                self.metricas.evaluacion(supuesto, tiene_derecho_potencial, inicio.elapsed());
             },
-            Err(zen_error) => {
-                // Intentar extraer información de errores de validación
-                if let Some(validation_errors) = Self::extract_validation_errors(&zen_error) {
-                    Err(ExcedenciaError::ValidationError(validation_errors))
-                } else {
-                    Err(ExcedenciaError::ZenEngineError(*zen_error))
-                }
-            }
+            Err(ExcedenciaError::ValidationError(errores)) => self.metricas.errores_validacion(errores),
+            Err(_) => {},
         }
+        evaluacion
     }
-    
-    // Función helper para extraer errores de validación del error de ZEN
-    fn extract_validation_errors(error: &EvaluationError) -> Option<Vec<ValidationError>> {
-        if let EvaluationError::NodeError(node_error) = error {
-            if let Some(errors) = Self::extract_from_node_error(node_error) {
-                return Some(errors);
-            }
-        }
+
+    /// Respuesta de la decisión principal, con la traza si se ha pedido
+    fn excedencia_response(model: &DecisionModel, result: DecisionGraphResponse, trace: bool) -> Result<(ExcedenciaResponse, Option<TrazaEvaluacion>), ExcedenciaError> {
+        let traza = match (trace, &result.trace) {
+            (false, _) => None,
+            (true, Some(nodos)) => Some(TrazaEvaluacion::from_trace(nodos.values(), result.performance.clone())),
+            (true, None) => return Err(ExcedenciaError::TrazaNoDisponibleError),
+        };
+
+        // Convertir el resultado de Variable a Value y luego deserializar
+        let result_value: serde_json::Value = result.result.into();
+        let mut response: ExcedenciaResponse = serde_json::from_value(result_value)?;
         
-        let error_str = format!("{:?}", error);
-        Self::extract_from_error_string(&error_str)
-    }
-    
-    fn extract_from_node_error(node_error: &NodeError) -> Option<Vec<ValidationError>> {
-        let source_str = format!("{:?}", node_error.source);
-        Self::extract_json_from_string(&source_str)
+        // Convertir ExcedenciaOutput a ExcedenciaOutputForSchema
+        let internal_output: ExcedenciaOutput = serde_json::from_value(
+            serde_json::to_value(&response.output)?
+        )?;
+        
+        response.output = ExcedenciaOutputForSchema {
+            descripcion: internal_output.descripcion,
+            importe_mensual: internal_output.importe_mensual,
+            requisitos_adicionales: internal_output.requisitos_adicionales,
+            supuesto: internal_output.supuesto,
+            tiene_derecho_potencial: internal_output.tiene_derecho_potencial,
+            errores: internal_output.errores,
+            advertencias: internal_output.advertencias,
+            duracion_maxima_meses: internal_output.duracion_maxima_meses,
+            estado: internal_output.estado,
+        };
+        response.modelo = Some(ModeloAplicado::from(model));
+        
+        Ok((response, traza))
     }
-    
-    fn extract_from_error_string(error_str: &str) -> Option<Vec<ValidationError>> {
-        Self::extract_json_from_string(error_str)
+
+    /// Evalúa todas las reglas de la tabla de supuestos y devuelve los supuestos cuyas
+    /// condiciones se cumplen, indicando el primero en orden de prelación
+    async fn evaluate_supuestos(&self, request: &ExcedenciaRequest, ejercicio: Option<i32>) -> Result<SupuestosAplicables, ExcedenciaError> {
+        let model = self.resolve_model(ejercicio)?;
+        let json_value = serde_json::to_value(request)?;
+        let decision = model.decision_supuestos.as_ref().unwrap_or(&model.decision);
+
+        // Las métricas cuentan el supuesto que se aplicaría por prelación
+        self.evaluate_graph(
+            &model,
+            decision,
+            json_value,
+            true,
+            |result| Self::supuestos_aplicables(request, &model, result),
+            |aplicables| (aplicables.supuesto_prelacion.as_deref().unwrap_or(SIN_SUPUESTO), aplicables.supuesto_prelacion.is_some()),
+        ).await
     }
-    
-    fn extract_json_from_string(text: &str) -> Option<Vec<ValidationError>> {
-        let patterns = vec![
-            (r#"{"source":{"errors":"#, r#""type":"Validation"}"#),
-            (r#"{"errors":"#, r#""type":"Validation"}"#),
-            (r#""errors":["#, r#"]"#),
-        ];
-        
-        for (start_pattern, end_pattern) in patterns {
-            if let Some(start) = text.find(start_pattern) {
-                let search_from = start + start_pattern.len();
-                if let Some(relative_end) = text[search_from..].find(end_pattern) {
-                    let end = search_from + relative_end + end_pattern.len();
-                    let json_candidate = &text[start..end];
-                    
-                    if let Ok(details) = serde_json::from_str::<ValidationErrorDetails>(json_candidate) {
-                        return Some(details.source.errors);
-                    }
-                    
-                    if let Some(errors) = Self::manual_extract_errors(text) {
-                        return Some(errors);
-                    }
-                }
+
+    /// Supuestos con derecho potencial entre las salidas de la tabla de supuestos
+    fn supuestos_aplicables(request: &ExcedenciaRequest, model: &DecisionModel, result: DecisionGraphResponse) -> Result<SupuestosAplicables, ExcedenciaError> {
+        // En modo `collect` la tabla devuelve la lista de salidas de las reglas que se cumplen,
+        // en el orden de la tabla; la traza del nodo contiene las reglas en el mismo orden.
+        // Si el modelo no tiene tabla de supuestos, la decisión normal devuelve una única salida
+        let salidas = match serde_json::Value::from(result.result) {
+            serde_json::Value::Array(salidas) => salidas,
+            salida => vec![salida],
+        };
+        let reglas = result.trace
+            .as_ref()
+            .and_then(|trace| trace.get(NODO_SUPUESTOS))
+            .and_then(|nodo| nodo.trace_data.clone())
+            .map(|trace_data| match trace_data {
+                serde_json::Value::Array(reglas) => reglas,
+                regla => vec![regla],
+            })
+            .unwrap_or_default();
+
+        let mut supuestos: Vec<SupuestoAplicable> = Vec::new();
+        for (index, salida) in salidas.into_iter().enumerate() {
+            let output: ExcedenciaOutputForSchema = serde_json::from_value(salida["output"].clone())?;
+            if !output.tiene_derecho_potencial || supuestos.iter().any(|s| s.supuesto == output.supuesto) {
+                continue;
             }
+            let regla = reglas.get(index).and_then(ReglaAplicada::from_trace_data);
+            supuestos.push(SupuestoAplicable {
+                supuesto: output.supuesto,
+                descripcion: output.descripcion,
+                importe_mensual: output.importe_mensual,
+                requisitos_adicionales: output.requisitos_adicionales,
+                estado: output.estado,
+                errores: output.errores,
+                regla: regla.map(|regla| regla.id).unwrap_or_default(),
+            });
         }
-        
-        Self::manual_extract_errors(text)
+
+        // Ante importes iguales se mantiene el orden de prelación
+        let supuesto_mas_favorable = supuestos.iter()
+            .rev()
+            .max_by_key(|s| s.importe_mensual)
+            .map(|s| s.supuesto.clone());
+
+        Ok(SupuestosAplicables {
+            supuesto_prelacion: supuestos.first().map(|s| s.supuesto.clone()),
+            supuesto_mas_favorable,
+            supuestos,
+            input: request.input.clone(),
+            modelo: ModeloAplicado::from(model),
+        })
     }
-    
-    fn manual_extract_errors(text: &str) -> Option<Vec<ValidationError>> {
-        if text.contains("is not one of") {
-            let lines: Vec<&str> = text.split(',').collect();
-            
-            let mut message = String::new();
-            let mut path = String::new();
-            
-            for line in lines {
-                if line.contains("\"message\":") {
-                    if let Some(start) = line.find("\"message\":\"") {
-                        let msg_start = start + "\"message\":\"".len();
-                        if let Some(end) = line[msg_start..].find("\"") {
-                            message = line[msg_start..msg_start + end].to_string();
-                        }
-                    }
+
+    /// Calcula el importe de una excedencia entre dos fechas, mes a mes. Cada año natural se
+    /// evalúa con la normativa aplicable a ese año, salvo que se fije un ejercicio, y los meses
+    /// parciales se prorratean por días naturales
+    async fn evaluate_periodo(&self, request: &ExcedenciaRequest, ejercicio: Option<i32>, fecha_inicio: NaiveDate, fecha_fin: NaiveDate) -> Result<ImportePeriodo, ExcedenciaError> {
+        let json_value = serde_json::to_value(request)?;
+
+        let mut evaluaciones: Vec<(i32, Arc<DecisionModel>, ExcedenciaOutputForSchema)> = Vec::new();
+        for year in fecha_inicio.year()..=fecha_fin.year() {
+            let model = match ejercicio {
+                Some(_) => self.resolve_model(ejercicio)?,
+                None => self.models.applicable(year).ok_or_else(|| ExcedenciaError::EjercicioNoDisponibleError {
+                    ejercicio: year,
+                    disponibles: self.models.ejercicios(),
+                })?,
+            };
+            let (response, _) = self.evaluate_model(&model, json_value.clone(), false).await?;
+            evaluaciones.push((year, model, response.output));
+        }
+
+        // La duración máxima es la del supuesto que se obtiene al inicio de la excedencia
+        let mut advertencias = Vec::new();
+        let inicial = &evaluaciones[0].2;
+        let duracion_maxima_meses = inicial.duracion_maxima_meses;
+        let fecha_fin_bonificable = match duracion_maxima_meses.and_then(|meses| fin_duracion_maxima(fecha_inicio, meses)) {
+            Some(fin_maximo) if fin_maximo < fecha_fin => {
+                advertencias.push(format!(
+                    "La duración máxima del supuesto {} es de {} meses: el periodo bonificable termina el {}",
+                    inicial.supuesto, duracion_maxima_meses.unwrap_or_default(), fin_maximo
+                ));
+                fin_maximo
+            },
+            Some(_) => fecha_fin,
+            None => {
+                if inicial.tiene_derecho_potencial {
+                    advertencias.push(format!(
+                        "El modelo de decisión no define una duración máxima para el supuesto {}: se calcula el periodo completo solicitado sin aplicar el límite de la normativa, que debe comprobarse aparte",
+                        inicial.supuesto
+                    ));
                 }
-                if line.contains("\"path\":") {
-                    if let Some(start) = line.find("\"path\":\"") {
-                        let path_start = start + "\"path\":\"".len();
-                        if let Some(end) = line[path_start..].find("\"") {
-                            path = line[path_start..path_start + end].to_string();
-                        }
+                fecha_fin
+            },
+        };
+
+        if inicial.estado == Some(EstadoResolucion::PendienteDocumentacion) {
+            advertencias.push(format!(
+                "El importe del supuesto {} está pendiente de acreditar: {}",
+                inicial.supuesto, inicial.errores.join("; ")
+            ));
+        }
+
+        // Los días posteriores a la duración máxima siguen en el calendario, con importe 0
+        let fuera_de_duracion = match fecha_fin_bonificable.succ_opt() {
+            Some(siguiente) if fecha_fin_bonificable < fecha_fin => tramos_mensuales(siguiente, fecha_fin),
+            _ => Vec::new(),
+        };
+
+        let mut meses: Vec<ImporteMes> = Vec::new();
+        let mut tramos: Vec<TramoEjercicio> = Vec::new();
+        for tramo in tramos_mensuales(fecha_inicio, fecha_fin_bonificable).into_iter().chain(fuera_de_duracion) {
+            let year = tramo.desde.year();
+            let Some((_, model, output)) = evaluaciones.iter().find(|(ejercicio, _, _)| *ejercicio == year) else {
+                continue;
+            };
+            let bonificable = tramo.hasta <= fecha_fin_bonificable;
+            let importe = if bonificable && output.tiene_derecho_potencial {
+                prorratear(output.importe_mensual, tramo.dias(), tramo.dias_mes())
+            } else {
+                0.0
+            };
+            meses.push(ImporteMes {
+                mes: tramo.desde.format("%Y-%m").to_string(),
+                ejercicio: year,
+                desde: tramo.desde,
+                hasta: tramo.hasta,
+                dias: tramo.dias(),
+                dias_mes: tramo.dias_mes(),
+                supuesto: output.supuesto.clone(),
+                importe_mensual: output.importe_mensual,
+                importe,
+            });
+            if !bonificable {
+                continue;
+            }
+
+            match tramos.last_mut() {
+                Some(actual) if actual.ejercicio == year => {
+                    actual.hasta = tramo.hasta;
+                    actual.importe = ((actual.importe + importe) * 100.0).round() / 100.0;
+                },
+                _ => {
+                    // Sin modelo para ese año se aplica el último anterior; con ejercicio fijado es lo pedido
+                    if ejercicio.is_none() && model.ejercicio != year {
+                        advertencias.push(format!(
+                            "No hay modelo de decisión para {}: se aplica la normativa de {}",
+                            year, model.ejercicio
+                        ));
                     }
-                }
+                    if !output.tiene_derecho_potencial {
+                        advertencias.push(format!(
+                            "Con la normativa aplicable en {} no hay derecho a la bonificación ({}): el importe de ese año es 0€",
+                            year, output.descripcion
+                        ));
+                    }
+                    if let Some(anterior) = tramos.last().filter(|anterior| anterior.supuesto != output.supuesto) {
+                        advertencias.push(format!(
+                            "El supuesto cambia de {} en {} a {} en {}",
+                            anterior.supuesto, anterior.ejercicio, output.supuesto, year
+                        ));
+                    }
+                    tramos.push(TramoEjercicio {
+                        ejercicio: year,
+                        modelo: ModeloAplicado::from(model.as_ref()),
+                        supuesto: output.supuesto.clone(),
+                        tiene_derecho_potencial: output.tiene_derecho_potencial,
+                        importe_mensual: output.importe_mensual,
+                        desde: tramo.desde,
+                        hasta: tramo.hasta,
+                        importe,
+                    });
+                },
             }
-            
-            if !message.is_empty() {
-                if path.is_empty() {
-                    path = "/input/unknown".to_string();
-                }
-                return Some(vec![ValidationError { message, path }]);
+        }
+
+        let importe_total = (meses.iter().map(|mes| mes.importe).sum::<f64>() * 100.0).round() / 100.0;
+        Ok(ImportePeriodo {
+            input: request.input.clone(),
+            fecha_inicio,
+            fecha_fin,
+            fecha_fin_bonificable,
+            duracion_maxima_meses,
+            tramos,
+            meses,
+            importe_total,
+            advertencias,
+        })
+    }
+
+    /// Convierte el error de zen-engine, recuperando los errores de validación de schema.
+    /// zen-engine se detiene en el primero, así que se completan con el validador de entrada del modelo
+    fn evaluation_error(model: &DecisionModel, json_value: &serde_json::Value, zen_error: EvaluationError) -> ExcedenciaError {
+        if let Some(validation_errors) = Self::extract_validation_errors(&zen_error) {
+            let input_errors = Self::validate_input(model, json_value);
+            if input_errors.is_empty() {
+                ExcedenciaError::ValidationError(validation_errors)
+            } else {
+                ExcedenciaError::ValidationError(input_errors)
             }
+        } else {
+            ExcedenciaError::ZenEngineError(zen_error)
         }
-        
-        None
+    }
+    
+    /// Valida la petición contra el schema del `inputNode` del modelo, devolviendo todos los errores
+    fn validate_input(model: &DecisionModel, request: &serde_json::Value) -> Vec<ValidationError> {
+        model.input_validator
+            .as_ref()
+            .map(|validator| validator.iter_errors(request).map(ValidationError::from).collect())
+            .unwrap_or_default()
+    }
+
+    /// Extrae los errores de validación de schema del error de zen-engine.
+    ///
+    /// El validador del `inputNode`/`outputNode` deja en `NodeError::source` el
+    /// `serde_json::Value` serializado del error, que se recupera por downcast.
+    fn extract_validation_errors(error: &EvaluationError) -> Option<Vec<ValidationError>> {
+        match error {
+            EvaluationError::NodeError(node_error) => Self::extract_from_node_error(node_error),
+            EvaluationError::Validation(value) => {
+                serde_json::from_value::<ValidationErrorSource>(value.as_ref().clone())
+                    .ok()
+                    .map(|source| source.errors)
+            },
+            _ => None,
+        }
+    }
+
+    fn extract_from_node_error(node_error: &NodeError) -> Option<Vec<ValidationError>> {
+        let value = node_error.source.downcast_ref::<serde_json::Value>()?;
+        let details: ValidationErrorDetails = serde_json::from_value(value.clone()).ok()?;
+        (details.error_type == "Validation").then_some(details.source.errors)
     }
 }
 
@@ -435,72 +1007,331 @@ impl ExcedenciaDecisionEngine {
 #[derive(Debug, Clone)]
 pub struct Calculadora {
     tool_router: ToolRouter<Self>,
+    engine: ExcedenciaDecisionEngine,
+    /// Decisiones GoRules del manifiesto, servidas como herramientas junto a las propias
+    decisiones: DecisionRegistry,
+    /// Cuenta la sesión como activa desde que se inicializa hasta que se suelta la última copia
+    sesion: Arc<std::sync::OnceLock<GuardaSesion>>,
+}
+
+impl Default for Calculadora {
+    fn default() -> Self {
+        Self::new(ModelRegistry::embedded())
+    }
 }
 
 #[tool_router]
 impl Calculadora {
-    pub fn new() -> Self {
+    pub fn new(models: ModelRegistry) -> Self {
         Self {
             tool_router: Self::tool_router(),
+            engine: ExcedenciaDecisionEngine::new(models),
+            decisiones: DecisionRegistry::default(),
+            sesion: Arc::default(),
         }
     }
 
-    /// Evalúa el derecho a ayuda para excedencia según la normativa de Navarra 2025
-    /// 
-    /// IMPORTANTE: Use los valores exactos especificados en cada parámetro.
-    /// 
-    /// EJEMPLOS DE LLAMADAS CORRECTAS:
-    /// 1. Padre soltero con recién nacido:
-    ///    - parentesco: "padre"
-    ///    - situacion: "parto"
-    ///    - familia_monoparental: true
-    ///    - numero_hijos: 1
-    /// 
-    /// 2. Cuidado de madre enferma (familia normal):
-    ///    - parentesco: "madre"  
-    ///    - situacion: "enfermedad"
-    ///    - familia_monoparental: false
-    ///    
-    /// 3. Tercer hijo en familia normal:
-    ///    - parentesco: "madre"
-    ///    - situacion: "parto"
-    ///    - familia_monoparental: false
-    ///    - numero_hijos: 3
-    #[tool(description = "Evalúa el derecho a ayuda para excedencia según la normativa de Navarra 2025. Determina supuesto (A-E) e importe (0€/500€/725€). SUPUESTOS: A=Cuidado familiar enfermo (725€), B=Tercer hijo+ (500€), C=Adopción (500€), D=Múltiple (500€), E=Monoparental (500€). USE VALORES EXACTOS: parentesco ('padre'/'madre'/'hijo'/'hija'/'conyuge'/'esposo'/'esposa'/'mujer'/'marido'), situacion ('parto'/'adopcion'/'acogimiento'/'parto_multiple'/'adopcion_multiple'/'acogimiento_multiple'/'enfermedad'/'accidente'), familia_monoparental (true/false), numero_hijos (número).")]
+    /// Sirve también las decisiones del manifiesto como herramientas
+    pub fn with_decisiones(mut self, decisiones: DecisionRegistry) -> Self {
+        self.decisiones = decisiones;
+        self
+    }
+
+    /// Nombres de las herramientas propias, que las decisiones del manifiesto no pueden usar
+    pub fn tool_names() -> Vec<String> {
+        Self::tool_router().list_all().into_iter().map(|tool| tool.name.to_string()).collect()
+    }
+
+    /// Usa unas métricas compartidas, normalmente las de todas las sesiones del proceso
+    pub fn with_metricas(mut self, metricas: Metricas) -> Self {
+        self.engine.metricas = metricas;
+        self
+    }
+
+    /// Evalúa el derecho a ayuda para excedencia según la normativa de Navarra
+    #[tool(description = "Evalúa el derecho a ayuda para excedencia según la normativa de Navarra. Determina el supuesto aplicable y su importe mensual. La descripción completa, con los supuestos y valores admitidos, se genera del modelo de decisión cargado.")]
     pub async fn evaluar_supuesto_excedencia(
         &self, 
         Parameters(direct_params): Parameters<ExcedenciaDirectParams>
     ) -> Result<CallToolResult, McpError> {
-        // Convert direct parameters to nested structure expected by the engine
-        let request = ExcedenciaRequest {
-            input: ExcedenciaInput {
-                parentesco: direct_params.parentesco,
-                situacion: direct_params.situacion,
-                familia_monoparental: direct_params.familia_monoparental,
-                numero_hijos: direct_params.numero_hijos,
-            }
+        let (request, ejercicio) = self.prepare_request(direct_params)?;
+        let result = self.run_blocking(move |engine| async move {
+            engine.evaluate_excedencia(&request, ejercicio).await
+        }).await;
+        Ok(Self::call_tool_result(result))
+    }
+
+    /// Evalúa todas las reglas de la tabla de supuestos en lugar de quedarse con la primera
+    #[tool(description = "Evalúa todos los supuestos cuyas condiciones se cumplen, no solo el primero, e indica el de prelación y el más favorable. La descripción completa, con los supuestos, se genera del modelo de decisión cargado.")]
+    pub async fn evaluar_supuestos_aplicables(
+        &self,
+        Parameters(direct_params): Parameters<ExcedenciaDirectParams>
+    ) -> Result<CallToolResult, McpError> {
+        let (request, ejercicio) = self.prepare_request(direct_params)?;
+        let result = self.run_blocking(move |engine| async move {
+            engine.evaluate_supuestos(&request, ejercicio).await
+        }).await;
+        Ok(Self::call_tool_result(result))
+    }
+
+    /// Evalúa muchos casos en una sola llamada, sin que un caso erróneo haga fallar el lote
+    #[tool(description = "Evalúa un lote de casos en una sola llamada, hasta el 'maxItems' del schema de 'casos', p. ej. la lista de empleados que solicitan excedencia. Cada caso lleva un 'id' propio y los mismos parámetros que evaluar_supuesto_excedencia. Devuelve el resultado o el error de cada caso, en el mismo orden, y un resumen con los casos por supuesto y el importe mensual total. Un caso con parámetros no válidos se informa como error sin afectar al resto.")]
+    pub async fn evaluar_lote(
+        &self,
+        Parameters(params): Parameters<EvaluarLoteParams>
+    ) -> Result<CallToolResult, McpError> {
+        if params.casos.len() > MAX_CASOS_LOTE {
+            return Err(McpError::invalid_params(
+                format!("El lote tiene {} casos; el máximo es {}", params.casos.len(), MAX_CASOS_LOTE),
+                None,
+            ));
+        }
+
+        let resultados = self.evaluate_lote(params).await;
+        let lote = ResultadoLote {
+            resumen: ResumenLote::from_resultados(&resultados),
+            resultados,
         };
+        Ok(Self::call_tool_result::<ResultadoLote>(Ok(Ok(lote))))
+    }
 
-        // Usar tokio::task::spawn_blocking para operaciones que no son Send
-        let result = tokio::task::spawn_blocking(move || {
-            // Crear un runtime tokio para la operación async dentro del bloque blocking
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.block_on(async move {
-                let engine = ExcedenciaDecisionEngine::new();
-                engine.evaluate_excedencia(&request).await
-            })
+    /// Calcula el importe total de una excedencia entre dos fechas con su calendario mensual
+    #[tool(description = "Calcula el importe total de la bonificación para una excedencia entre fecha_inicio y fecha_fin (AAAA-MM-DD, ambas incluidas). Devuelve el calendario mes a mes: los meses parciales se prorratean por días naturales, se aplica la duración máxima del supuesto solo si el modelo de decisión la define (el modelo incluido no la define: el periodo no se limita y se advierte en 'advertencias') y, si la excedencia abarca varios años, cada año se evalúa con su normativa (tramos por ejercicio). Mismos parámetros que evaluar_supuesto_excedencia más las dos fechas; si se indica 'ejercicio' se aplica esa normativa a todo el periodo.")]
+    pub async fn calcular_importe_periodo(
+        &self,
+        Parameters(params): Parameters<ImportePeriodoParams>
+    ) -> Result<CallToolResult, McpError> {
+        let (fecha_inicio, fecha_fin) = (params.fecha_inicio, params.fecha_fin);
+        if fecha_fin < fecha_inicio {
+            return Err(McpError::invalid_params(
+                format!("La fecha de fin ({}) es anterior a la de inicio ({})", fecha_fin, fecha_inicio),
+                None,
+            ));
+        }
+        if fecha_fin.year() - fecha_inicio.year() >= MAX_ANOS_PERIODO {
+            return Err(McpError::invalid_params(
+                format!("El periodo no puede abarcar más de {} años naturales", MAX_ANOS_PERIODO),
+                None,
+            ));
+        }
+
+        let (request, ejercicio) = self.prepare_request(params.parametros)?;
+        let result = self.run_blocking(move |engine| async move {
+            engine.evaluate_periodo(&request, ejercicio, fecha_inicio, fecha_fin).await
         }).await;
-        
+        Ok(Self::call_tool_result(result))
+    }
+
+    /// Explica por qué se obtiene un resultado: regla aplicada, parentesco y nodos evaluados
+    #[tool(description = "Evalúa igual que evaluar_supuesto_excedencia y además explica el resultado a partir de la traza del modelo de decisión: regla aplicada de la tabla de supuestos (id, descripción, condiciones y valores comparados), resultado de la comprobación de parentesco (parentesco_valido) y nodos evaluados en orden con su duración. Úselo para responder preguntas como '¿por qué es el supuesto B y no el E?'. Las reglas se evalúan en orden y se aplica la primera que coincide.")]
+    pub async fn explicar_evaluacion(
+        &self,
+        Parameters(direct_params): Parameters<ExcedenciaDirectParams>
+    ) -> Result<CallToolResult, McpError> {
+        let (request, ejercicio) = self.prepare_request(direct_params)?;
+        let result = self.run_blocking(move |engine| async move {
+            engine.explain_excedencia(&request, ejercicio).await
+        }).await;
+        Ok(Self::call_tool_result(result))
+    }
+}
+
+impl Calculadora {
+    /// Evalúa una decisión del manifiesto con los argumentos de la llamada como contexto.
+    /// Si la decisión declara schema de salida, el resultado va además como contenido estructurado
+    async fn call_decision(&self, herramienta: Arc<HerramientaDecision>, arguments: Option<JsonObject>) -> Result<CallToolResult, McpError> {
+        let arguments = serde_json::Value::Object(arguments.unwrap_or_default());
+        if let Some(validator) = &herramienta.input_validator {
+            let validation_errors: Vec<ValidationError> = validator.iter_errors(&arguments).map(ValidationError::from).collect();
+            if !validation_errors.is_empty() {
+                self.engine.metricas.errores_validacion(&validation_errors);
+                return Err(invalid_params_error(validation_errors));
+            }
+        }
+
+        let evaluada = herramienta.clone();
+        let (result, duracion) = en_hilo_blocking(move || async move {
+            let inicio = std::time::Instant::now();
+            let result = evaluada.evaluate(arguments).await;
+            (result, inicio.elapsed())
+        }).await
+            .map_err(|e| McpError::internal_error(format!("Error interno: {}", e), None))?;
+
+        let nombre = herramienta.tool.name.as_ref();
+        match result {
+            Ok(value) => {
+                self.engine.metricas.evaluacion_decision(nombre, true, duracion);
+                let json_str = serde_json::to_string_pretty(&value).map_err(|e| McpError::internal_error(
+                    format!("Error al serializar la respuesta: {}", e), None
+                ))?;
+                let mut call_result = CallToolResult::success(vec![Content::text(json_str)]);
+                if herramienta.tool.output_schema.is_some() {
+                    call_result.structured_content = Some(value);
+                }
+                Ok(call_result)
+            },
+            Err(zen_error) => {
+                let error = match ExcedenciaDecisionEngine::extract_validation_errors(&zen_error) {
+                    Some(validation_errors) => {
+                        self.engine.metricas.errores_validacion(&validation_errors);
+                        ExcedenciaError::ValidationError(validation_errors)
+                    },
+                    None => {
+                        self.engine.metricas.evaluacion_decision(nombre, false, duracion);
+                        ExcedenciaError::ZenEngineError(*zen_error)
+                    },
+                };
+                Ok(CallToolResult::error(vec![Content::text(format!("Error al evaluar: {}", error))]))
+            },
+        }
+    }
+
+    /// Rechaza los valores de parentesco y situación que no se pueden interpretar con el mismo
+    /// error que la validación del schema, y los cuenta en las métricas de validación
+    fn validar_valores(&self, arguments: Option<&JsonObject>) -> Result<(), McpError> {
+        let validation_errors = arguments.map(ValidationError::de_valores).unwrap_or_default();
+        if validation_errors.is_empty() {
+            return Ok(());
+        }
+        self.engine.metricas.errores_validacion(&validation_errors);
+        Err(invalid_params_error(validation_errors))
+    }
+
+    /// Convierte los parámetros planos de la herramienta en la petición anidada del motor y
+    /// rechaza los valores que no admite el schema de entrada del modelo antes de evaluar.
+    /// Si el ejercicio no está disponible, el motor devuelve el error correspondiente
+    fn prepare_request(&self, direct_params: ExcedenciaDirectParams) -> Result<(ExcedenciaRequest, Option<i32>), McpError> {
+        let (request, ejercicio) = direct_params.into_request();
+
+        if let Ok(model) = self.engine.resolve_model(ejercicio) {
+            let json_value = serde_json::to_value(&request).map_err(|e| McpError::internal_error(
+                format!("Error de serialización: {}", e), None
+            ))?;
+            let validation_errors = ExcedenciaDecisionEngine::validate_input(&model, &json_value);
+            if !validation_errors.is_empty() {
+                self.engine.metricas.errores_validacion(&validation_errors);
+                return Err(invalid_params_error(validation_errors));
+            }
+        }
+
+        Ok((request, ejercicio))
+    }
+
+    /// Evalúa los casos del lote repartidos entre varios hilos blocking. Cada caso se
+    /// evalúa con el modelo resuelto al empezar, así una recarga no mezcla versiones
+    async fn evaluate_lote(&self, params: EvaluarLoteParams) -> Vec<ResultadoCaso> {
+        // Cada posición guarda el resultado o, mientras está pendiente de evaluar, el id del caso
+        let mut resultados: Vec<Result<ResultadoCaso, String>> = Vec::with_capacity(params.casos.len());
+        let mut pendientes = Vec::new();
+
+        for (index, entrada) in params.casos.into_iter().enumerate() {
+            let caso = match entrada.0 {
+                Ok(caso) => caso,
+                Err(no_valido) => {
+                    self.engine.metricas.errores_validacion(&no_valido.errores_validacion);
+                    resultados.push(Ok(ResultadoCaso {
+                        errores_validacion: no_valido.errores_validacion,
+                        ..ResultadoCaso::error(no_valido.id, no_valido.error)
+                    }));
+                    continue;
+                },
+            };
+            let (request, ejercicio) = caso.parametros.into_request();
+            let resultado = self.engine.resolve_model(ejercicio.or(params.ejercicio))
+                .and_then(|model| Ok((serde_json::to_value(&request)?, model)));
+            match resultado {
+                Ok((json_value, model)) => {
+                    let validation_errors = ExcedenciaDecisionEngine::validate_input(&model, &json_value);
+                    if validation_errors.is_empty() {
+                        resultados.push(Err(caso.id.clone()));
+                        pendientes.push((index, caso.id, request, json_value, model));
+                    } else {
+                        self.engine.metricas.errores_validacion(&validation_errors);
+                        resultados.push(Ok(ResultadoCaso {
+                            errores_validacion: validation_errors,
+                            ..ResultadoCaso::error(caso.id, "Parámetros no válidos".to_string())
+                        }));
+                    }
+                },
+                Err(e) => resultados.push(Ok(ResultadoCaso::error(caso.id, e.to_string()))),
+            }
+        }
+
+        let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = pendientes.len().div_ceil(workers).max(1);
+        let mut chunks = Vec::new();
+        while !pendientes.is_empty() {
+            let resto = pendientes.split_off(chunk_size.min(pendientes.len()));
+            chunks.push(std::mem::replace(&mut pendientes, resto));
+        }
+
+        let evaluaciones = chunks.into_iter().map(|chunk| {
+            self.run_blocking(move |engine| async move {
+                let engine = &engine;
+                let evaluaciones = chunk.into_iter().map(|(index, id, request, json_value, model)| async move {
+                    let resultado = engine.evaluate_model(&model, json_value, false).await;
+                    let resultado = match resultado {
+                        Ok((mut response, _)) => {
+                            response.input = Some(request.input);
+                            ResultadoCaso { id, resultado: Some(response), error: None, errores_validacion: Vec::new() }
+                        },
+                        Err(ExcedenciaError::ValidationError(errores)) => ResultadoCaso {
+                            errores_validacion: errores,
+                            ..ResultadoCaso::error(id, "Parámetros no válidos".to_string())
+                        },
+                        Err(e) => ResultadoCaso::error(id, format!("Error al evaluar: {}", e)),
+                    };
+                    (index, resultado)
+                });
+                Ok(futures::future::join_all(evaluaciones).await)
+            })
+        });
+
+        for evaluacion in futures::future::join_all(evaluaciones).await {
+            match evaluacion {
+                Ok(Ok(evaluados)) => {
+                    for (index, resultado) in evaluados {
+                        resultados[index] = Ok(resultado);
+                    }
+                },
+                Ok(Err(e)) => tracing::error!("Error al evaluar el lote: {}", e),
+                Err(join_error) => tracing::error!("Error interno al evaluar el lote: {}", join_error),
+            }
+        }
+
+        // Los casos de un bloque que no ha terminado se informan como error, sin perder el resto
+        resultados.into_iter()
+            .map(|resultado| resultado.unwrap_or_else(|id| ResultadoCaso::error(id, "Error interno al evaluar el caso".to_string())))
+            .collect()
+    }
+
+    /// Evalúa con una copia del motor en un hilo blocking, ver [`en_hilo_blocking`]
+    async fn run_blocking<T, F, Fut>(&self, evaluation: F) -> Result<Result<T, ExcedenciaError>, tokio::task::JoinError>
+    where
+        T: Send + 'static,
+        F: FnOnce(ExcedenciaDecisionEngine) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<T, ExcedenciaError>>,
+    {
+        let engine = self.engine.clone();
+        en_hilo_blocking(move || evaluation(engine)).await
+    }
+
+    fn call_tool_result<T: Serialize>(result: Result<Result<T, ExcedenciaError>, tokio::task::JoinError>) -> CallToolResult {
         match result {
             Ok(eval_result) => {
                 match eval_result {
                     Ok(response) => {
-                        // Serialize the response to JSON and return as success
-                        match serde_json::to_string_pretty(&response) {
-                            Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
-                            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                        // La respuesta va como texto JSON, para los clientes que solo leen `content`,
+                        // y como contenido estructurado conforme al `output_schema` de la herramienta
+                        match (serde_json::to_string_pretty(&response), serde_json::to_value(&response)) {
+                            (Ok(json_str), Ok(value)) => {
+                                let mut call_result = CallToolResult::success(vec![Content::text(json_str)]);
+                                call_result.structured_content = Some(value);
+                                call_result
+                            },
+                            (Err(e), _) | (_, Err(e)) => CallToolResult::error(vec![Content::text(format!(
                                 "Error al serializar la respuesta: {}", e
-                            ))]))
+                            ))])
                         }
                     },
                     Err(e) => {
@@ -508,49 +1339,195 @@ impl Calculadora {
                             ExcedenciaError::ValidationError(validation_errors) => {
                                 let mut msg = "Errores de validación:\n".to_string();
                                 for error in validation_errors {
-                                    msg.push_str(&format!("  - Campo '{}': {}\n", error.path, error.message));
+                                    msg.push_str(&format!("  - {}\n", error));
                                 }
                                 msg
                             },
                             _ => format!("Error al evaluar: {}", e)
                         };
-                        Ok(CallToolResult::error(vec![Content::text(error_msg)]))
+                        CallToolResult::error(vec![Content::text(error_msg)])
                     }
                 }
             },
             Err(join_error) => {
-                Ok(CallToolResult::error(vec![Content::text(format!(
+                CallToolResult::error(vec![Content::text(format!(
                     "Error interno: {}", join_error
-                ))]))
+                ))])
             }
         }
     }
+
+    /// Herramientas con los `enum` del schema de entrada del modelo vigente y las descripciones de
+    /// `evaluar_supuesto_excedencia` y `evaluar_supuestos_aplicables` generadas del modelo, de modo que los clientes vean los
+    /// valores y supuestos que realmente aplica el motor tras cada recarga. Cada herramienta
+    /// propia declara además el schema de su resultado
+    fn list_all_tools(&self) -> Vec<Tool> {
+        let model = self.modelo_vigente();
+        let model_schema = model.as_ref().and_then(|model| model.input_schema.clone());
+
+        self.tool_router.list_all()
+            .into_iter()
+            .map(|mut tool| {
+                if let Some(model_schema) = &model_schema {
+                    tool.input_schema = Arc::new(apply_model_enums(&tool.input_schema, model_schema));
+                }
+                match tool.name.as_ref() {
+                    "evaluar_supuesto_excedencia" => tool.description = Some(descripcion_evaluar(model.as_deref()).into()),
+                    "evaluar_supuestos_aplicables" => tool.description = Some(descripcion_supuestos_aplicables(model.as_deref()).into()),
+                    _ => {},
+                }
+                tool.output_schema = Self::output_schema(&tool.name);
+                tool
+            })
+            .chain(self.decisiones.tools())
+            .collect()
+    }
+
+    /// Schema del resultado de cada herramienta propia. No se declara en `#[tool]` porque el router
+    /// rechaza entonces los resultados sin `structured_content`, y los errores de evaluación se
+    /// devuelven solo como texto con `is_error`
+    fn output_schema(name: &str) -> Option<Arc<JsonObject>> {
+        match name {
+            "evaluar_supuesto_excedencia" => Some(cached_schema_for_type::<ExcedenciaResponse>()),
+            "evaluar_supuestos_aplicables" => Some(cached_schema_for_type::<SupuestosAplicables>()),
+            "evaluar_lote" => Some(cached_schema_for_type::<ResultadoLote>()),
+            "calcular_importe_periodo" => Some(cached_schema_for_type::<ImportePeriodo>()),
+            "explicar_evaluacion" => Some(cached_schema_for_type::<ExplicacionEvaluacion>()),
+            _ => None,
+        }
+    }
+
+    /// Modelo vigente o, si todos son de ejercicios futuros, el último cargado
+    fn modelo_vigente(&self) -> Option<Arc<DecisionModel>> {
+        self.engine.models.current()
+            .or_else(|| self.engine.models.ejercicios().last().and_then(|last| self.engine.models.get(*last)))
+    }
+}
+
+/// La evaluación de zen-engine no es Send: se ejecuta en un hilo blocking
+/// reutilizando el runtime actual en lugar de crear uno por petición
+async fn en_hilo_blocking<T, F, Fut>(evaluation: F) -> Result<T, tokio::task::JoinError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = T>,
+{
+    let handle = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || handle.block_on(evaluation())).await
+}
+
+/// Identidad del cliente autenticado por el servidor HTTP. rmcp deja las partes de la
+/// petición HTTP en las extensiones del contexto; en stdio no hay identidad
+fn identidad(context: &RequestContext<RoleServer>) -> Option<&Identidad> {
+    context
+        .extensions
+        .get::<axum::http::request::Parts>()
+        .and_then(|parts| parts.extensions.get::<Identidad>())
+}
+
+/// Resultado de herramienta con `is_error` si la identidad no tiene el permiso que exige la
+/// herramienta. Es un error de la llamada, no de protocolo: la petición MCP es correcta
+fn permiso_insuficiente(identidad: &Identidad, name: &str) -> Option<CallToolResult> {
+    let error = exigir(identidad, Permiso::de_herramienta(name)).err()?;
+    Some(CallToolResult::error(vec![Content::text(format!("Permiso insuficiente: {}", error))]))
+}
+
+/// Error MCP `invalid_params` con todos los errores de validación del schema de entrada
+fn invalid_params_error(validation_errors: Vec<ValidationError>) -> McpError {
+    let mut msg = "Parámetros no válidos:\n".to_string();
+    for error in &validation_errors {
+        msg.push_str(&format!("  - {}\n", error));
+    }
+    McpError::invalid_params(msg, Some(serde_json::json!({ "errores": validation_errors })))
 }
 
-#[tool_handler]
 impl ServerHandler for Calculadora {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(sin_permiso) = identidad(&context).and_then(|identidad| permiso_insuficiente(identidad, &request.name)) {
+            return Ok(sin_permiso);
+        }
+        // Solo se cuentan llamadas permitidas a herramientas existentes, para no crear series por nombres arbitrarios
+        let decision = self.decisiones.get(&request.name);
+        if self.tool_router.has_route(&request.name) || decision.is_some() {
+            self.engine.metricas.llamada_herramienta(&request.name);
+        }
+        if let Some(herramienta) = decision {
+            return self.call_decision(herramienta, request.arguments).await;
+        }
+        self.validar_valores(request.arguments.as_ref())?;
+        let tcc = ToolCallContext::new(self, request, context);
+        self.tool_router.call(tcc).await
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        // Cada cliente ve solo las herramientas que sus permisos le dejan llamar
+        let tools = self.list_all_tools()
+            .into_iter()
+            .filter(|tool| identidad(&context).is_none_or(|identidad| identidad.permite(Permiso::de_herramienta(&tool.name))))
+            .collect();
+        Ok(ListToolsResult::with_all_items(tools))
+    }
+
+    // Los recursos y prompts no exigen permisos: solo publican el catálogo y el schema del
+    // modelo, que ya se pueden deducir de `tools/list`, y no evalúan ningún caso
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        Ok(ListResourcesResult::with_all_items(recursos::list_resources(&self.engine.models)))
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        Ok(ListResourceTemplatesResult::with_all_items(recursos::list_resource_templates()))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let contents = recursos::read_resource(&self.engine.models, &request.uri)?;
+        Ok(ReadResourceResult { contents: vec![contents] })
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, McpError> {
+        Ok(ListPromptsResult::with_all_items(prompts::list_prompts()))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        prompts::get_prompt(&self.engine.models, &request.name, request.arguments.as_ref())
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
-            instructions: Some(
-                "Calculadora de ayudas para excedencia según la normativa de Navarra 2025. \
-                 \n\n** INSTRUCCIONES IMPORTANTES PARA USO DE HERRAMIENTAS **\
-                 \n\n1. SIEMPRE use los valores EXACTOS especificados para cada parámetro, CASE SENSITIVE\
-                 \n\n2. Para parentesco, use ÚNICAMENTE: 'padre', 'madre', 'hijo', 'hija', 'conyuge', 'esposo', 'esposa', 'mujer', 'marido'\
-                 \n\n3. Para situacion, use ÚNICAMENTE: 'parto', 'adopcion', 'acogimiento', 'parto_multiple', 'adopcion_multiple', 'acogimiento_multiple', 'enfermedad', 'accidente'\
-                 \n\n4. Para familia_monoparental, use ÚNICAMENTE: true (para familias monoparentales) o false (para familias no monoparentales)\
-                 \n\n5. Para numero_hijos, use números enteros (ej: 1, 2, 3, 4, 5)\
-                 \n\nEJEMPLOS DE USO CORRECTO:\
-                 \n• Padre soltero con bebé: parentesco='padre', situacion='parto', familia_monoparental=true, numero_hijos=1\
-                 \n• Hijo cuidando a padre enfermo: parentesco='padre', situacion='enfermedad', familia_monoparental=false\
-                 \n• Familia con tercer hijo: parentesco='madre', situacion='parto', familia_monoparental=false, numero_hijos=3\
-                 \n\nSUPUESTOS EVALUADOS:\
-                 \nA) Cuidado familiar enfermo/accidentado (725€/mes)\
-                 \nB) Tercer hijo+ con recién nacido (500€/mes)\
-                 \nC) Adopción/acogimiento (500€/mes)\
-                 \nD) Partos/adopciones múltiples (500€/mes)\
-                 \nE) Familias monoparentales (500€/mes)".into()
-            ),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            instructions: Some(instrucciones(self.modelo_vigente().as_deref())),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .enable_resources()
+                .enable_prompts()
+                .build(),
             server_info: rmcp::model::Implementation {
                 name: "bon-calculadora".to_string(),
                 version: "1.0.0".to_string(),
@@ -558,87 +1535,769 @@ impl ServerHandler for Calculadora {
             ..Default::default()
         }
     }
+
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        // Registrar la sesión para avisarla si la recarga del modelo cambia el schema
+        self.engine.models.register_peer(context.peer);
+        let _ = self.sesion.set(self.engine.metricas.sesion_activa());
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    impl ExcedenciaDirectParams {
+        /// Parámetros con los campos obligatorios y el resto sin indicar
+        fn obligatorios(parentesco: Parentesco, situacion: Situacion, familia_monoparental: bool) -> Self {
+            Self {
+                parentesco,
+                situacion,
+                familia_monoparental,
+                numero_hijos: None,
+                hijos: None,
+                hospitalizacion: None,
+                fecha_hospitalizacion: None,
+                cuidado_continuado: None,
+                duracion_acogimiento_meses: None,
+                fecha_inicio_acogimiento: None,
+                fecha_fin_acogimiento: None,
+                ejercicio: None,
+            }
+        }
+    }
+
+    fn result_text(call_result: &CallToolResult) -> String {
+        let content = call_result.content.as_ref().expect("el resultado debe tener contenido");
+        content[0].as_text().expect("el contenido debe ser texto").text.clone()
+    }
+
+    /// Salida de `evaluar_supuesto_excedencia` para unos parámetros JSON como los que envía el cliente
+    async fn evaluar(params: serde_json::Value) -> ExcedenciaOutputForSchema {
+        let direct_params: ExcedenciaDirectParams = serde_json::from_value(params).unwrap();
+        let call_result = Calculadora::default().evaluar_supuesto_excedencia(Parameters(direct_params)).await.unwrap();
+        assert_eq!(call_result.is_error, Some(false));
+        serde_json::from_str::<ExcedenciaResponse>(&result_text(&call_result)).unwrap().output
+    }
+
     #[tokio::test]
     async fn test_calculadora_supuesto_a() {
-        let calculadora = Calculadora::new();
-        let direct_params = ExcedenciaDirectParams {
-            parentesco: "madre".to_string(),
-            situacion: "enfermedad".to_string(),
-            familia_monoparental: false,
-            numero_hijos: None,
-        };
+        let calculadora = Calculadora::default();
+        let direct_params = ExcedenciaDirectParams::obligatorios(Parentesco::Madre, Situacion::Enfermedad, false);
         
-        let result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await;
-        match result {
-            Ok(call_result) => {
-                // Check if it's a success result
-                println!("Resultado Supuesto A: {:?}", call_result);
-            },
-            Err(e) => panic!("Error inesperado: {}", e),
-        }
+        let call_result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await.unwrap();
+        let output = serde_json::from_str::<ExcedenciaResponse>(&result_text(&call_result)).unwrap().output;
+        assert_eq!(output.supuesto, "A");
+        assert!(output.tiene_derecho_potencial);
+        assert_eq!(output.importe_mensual, 725);
     }
 
     #[tokio::test] 
     async fn test_calculadora_supuesto_e() {
-        let calculadora = Calculadora::new();
+        let calculadora = Calculadora::default();
         let direct_params = ExcedenciaDirectParams {
-            parentesco: "madre".to_string(),
-            situacion: "parto".to_string(),
-            familia_monoparental: true,
             numero_hijos: Some(1.0),
+            ..ExcedenciaDirectParams::obligatorios(Parentesco::Madre, Situacion::Parto, true)
         };
         
-        let result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await;
-        match result {
-            Ok(call_result) => {
-                println!("Resultado Supuesto E: {:?}", call_result);
-            },
-            Err(e) => panic!("Error inesperado: {}", e),
-        }
+        let call_result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await.unwrap();
+        let output = serde_json::from_str::<ExcedenciaResponse>(&result_text(&call_result)).unwrap().output;
+        assert_eq!(output.supuesto, "E");
+        assert!(output.tiene_derecho_potencial);
+        assert_eq!(output.importe_mensual, 500);
     }
 
     #[tokio::test]
     async fn test_calculadora_supuesto_b() {
-        let calculadora = Calculadora::new();
+        let calculadora = Calculadora::default();
         let direct_params = ExcedenciaDirectParams {
-            parentesco: "madre".to_string(),
-            situacion: "parto".to_string(),
-            familia_monoparental: false,
             numero_hijos: Some(3.0), // Tercer hijo
+            ..ExcedenciaDirectParams::obligatorios(Parentesco::Madre, Situacion::Parto, false)
         };
         
-        let result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await;
-        match result {
-            Ok(call_result) => {
-                println!("Resultado Supuesto B: {:?}", call_result);
-            },
-            Err(e) => panic!("Error inesperado: {}", e),
+        let call_result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await.unwrap();
+        let output = serde_json::from_str::<ExcedenciaResponse>(&result_text(&call_result)).unwrap().output;
+        assert_eq!(output.supuesto, "B");
+        assert!(output.tiene_derecho_potencial);
+        assert_eq!(output.importe_mensual, 500);
+    }
+
+    #[tokio::test]
+    async fn test_calculadora_supuesto_b_edades_hijos() {
+        // Dos menores de 6 años y un tercero de 8 con discapacidad del 40%
+        let output = evaluar(serde_json::json!({
+            "parentesco": "madre", "situacion": "parto", "familia_monoparental": false,
+            "hijos": [
+                {"fecha_nacimiento": "2025-03-01"},
+                {"fecha_nacimiento": "2021-06-15"},
+                {"fecha_nacimiento": "2017-01-10", "grado_discapacidad": 40}
+            ]
+        })).await;
+        assert_eq!(output.supuesto, "B");
+        assert!(output.tiene_derecho_potencial);
+        assert_eq!(output.importe_mensual, 500);
+        assert!(output.advertencias.is_empty());
+
+        // Solo el recién nacido es menor de 6 años (el mayor de 8 no tiene discapacidad)
+        let output = evaluar(serde_json::json!({
+            "parentesco": "madre", "situacion": "parto", "familia_monoparental": false,
+            "hijos": [
+                {"fecha_nacimiento": "2025-03-01"},
+                {"fecha_nacimiento": "2019-03-01"},
+                {"fecha_nacimiento": "2017-01-10", "grado_discapacidad": "20"}
+            ]
+        })).await;
+        assert_eq!(output.supuesto, "B");
+        assert!(!output.tiene_derecho_potencial);
+        assert_eq!(output.importe_mensual, 0);
+        assert!(output.errores[0].contains("Menos de 2 hijos"));
+    }
+
+    #[tokio::test]
+    async fn test_calculadora_supuesto_b_sin_hijos_advierte() {
+        let output = evaluar(serde_json::json!({
+            "parentesco": "madre", "situacion": "parto", "familia_monoparental": false, "numero_hijos": 3
+        })).await;
+        assert_eq!(output.supuesto, "B");
+        assert!(output.tiene_derecho_potencial);
+        assert_eq!(output.estado, Some(EstadoResolucion::PendienteDocumentacion));
+        assert!(output.advertencias[0].contains("fechas de nacimiento"));
+
+        // Una lista de hijos vacía equivale a no indicarla
+        let output = evaluar(serde_json::json!({
+            "parentesco": "madre", "situacion": "parto", "familia_monoparental": false, "numero_hijos": 3, "hijos": []
+        })).await;
+        assert_eq!(output.supuesto, "B");
+        assert_eq!(output.estado, Some(EstadoResolucion::PendienteDocumentacion));
+        assert!(output.advertencias[0].contains("fechas de nacimiento"));
+    }
+
+    #[tokio::test]
+    async fn test_calculadora_supuesto_d_multiple() {
+        for situacion in ["parto_multiple", "adopcion_multiple", "acogimiento_multiple", "acogida múltiple"] {
+            let output = evaluar(serde_json::json!({
+                "parentesco": "madre", "situacion": situacion, "familia_monoparental": false, "numero_hijos": 2
+            })).await;
+            assert_eq!(output.supuesto, "D", "{}", situacion);
+            assert!(output.tiene_derecho_potencial);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calculadora_supuesto_a_requisitos() {
+        let output = evaluar(serde_json::json!({
+            "parentesco": "padre", "situacion": "enfermedad", "familia_monoparental": false,
+            "fecha_hospitalizacion": "2025-02-20", "cuidado_continuado": "true"
+        })).await;
+        assert_eq!(output.supuesto, "A");
+        assert_eq!(output.estado, Some(EstadoResolucion::Reconocido));
+        assert!(output.tiene_derecho_potencial);
+        assert_eq!(output.importe_mensual, 725);
+        assert!(output.errores.is_empty());
+
+        let output = evaluar(serde_json::json!({
+            "parentesco": "padre", "situacion": "enfermedad", "familia_monoparental": false,
+            "hospitalizacion": false, "cuidado_continuado": true
+        })).await;
+        assert_eq!(output.supuesto, "A");
+        assert_eq!(output.estado, Some(EstadoResolucion::Denegado));
+        assert!(!output.tiene_derecho_potencial);
+        assert_eq!(output.importe_mensual, 0);
+        assert_eq!(output.errores, ["La persona no ha sido hospitalizada"]);
+    }
+
+    #[tokio::test]
+    async fn test_calculadora_supuesto_a_pendiente_documentacion() {
+        let output = evaluar(serde_json::json!({
+            "parentesco": "padre", "situacion": "enfermedad", "familia_monoparental": false, "hospitalizacion": true
+        })).await;
+        assert_eq!(output.supuesto, "A");
+        assert_eq!(output.estado, Some(EstadoResolucion::PendienteDocumentacion));
+        assert!(output.tiene_derecho_potencial);
+        assert_eq!(output.errores.len(), 1);
+        assert!(output.errores[0].contains("cuidado_continuado"));
+
+        let output = evaluar(serde_json::json!({"parentesco": "padre", "situacion": "enfermedad", "familia_monoparental": false})).await;
+        assert_eq!(output.estado, Some(EstadoResolucion::PendienteDocumentacion));
+        assert_eq!(output.errores.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_calculadora_supuesto_c_duracion_acogimiento() {
+        let output = evaluar(serde_json::json!({"parentesco": "madre", "situacion": "adopcion", "familia_monoparental": false})).await;
+        assert_eq!((output.supuesto.as_str(), output.estado), ("C", Some(EstadoResolucion::Reconocido)));
+        assert_eq!(output.importe_mensual, 500);
+
+        let output = evaluar(serde_json::json!({
+            "parentesco": "madre", "situacion": "acogimiento", "familia_monoparental": false, "duracion_acogimiento_meses": "18"
+        })).await;
+        assert_eq!((output.supuesto.as_str(), output.estado), ("C", Some(EstadoResolucion::Reconocido)));
+        assert!(output.tiene_derecho_potencial);
+
+        // Un año justo no supera el año de duración
+        let output = evaluar(serde_json::json!({
+            "parentesco": "madre", "situacion": "acogimiento", "familia_monoparental": false,
+            "fecha_inicio_acogimiento": "2025-01-15",
+            "fecha_fin_acogimiento": "2026-01-15"
+        })).await;
+        assert_eq!((output.supuesto.as_str(), output.estado), ("C", Some(EstadoResolucion::Denegado)));
+        assert!(!output.tiene_derecho_potencial);
+        assert_eq!(output.importe_mensual, 0);
+        assert!(output.errores[0].contains("no dura más de un año"));
+
+        let output = evaluar(serde_json::json!({
+            "parentesco": "madre", "situacion": "acogimiento", "familia_monoparental": false,
+            "fecha_inicio_acogimiento": "2025-01-15",
+            "fecha_fin_acogimiento": "2026-01-16"
+        })).await;
+        assert_eq!(output.estado, Some(EstadoResolucion::Reconocido));
+
+        let output = evaluar(serde_json::json!({"parentesco": "madre", "situacion": "acogimiento", "familia_monoparental": false})).await;
+        assert_eq!((output.supuesto.as_str(), output.estado), ("C", Some(EstadoResolucion::PendienteDocumentacion)));
+        assert!(output.errores[0].contains("duracion_acogimiento_meses"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_calculadora_concurrent_calls_share_decision() {
+        let calculadora = Calculadora::default();
+        let calls = (0..16).map(|i| {
+            let calculadora = calculadora.clone();
+            tokio::spawn(async move {
+                let direct_params = ExcedenciaDirectParams {
+                    numero_hijos: Some(3.0),
+                    ..ExcedenciaDirectParams::obligatorios(Parentesco::Madre, Situacion::Parto, i % 2 == 0)
+                };
+                calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await
+            })
+        });
+
+        for call in futures::future::join_all(calls).await {
+            let call_result = call.unwrap().unwrap();
+            assert_ne!(call_result.is_error, Some(true));
         }
     }
 
+    #[tokio::test]
+    async fn test_calculadora_ejercicio() {
+        let calculadora = Calculadora::default();
+        let direct_params: ExcedenciaDirectParams = serde_json::from_value(serde_json::json!({
+            "parentesco": "madre",
+            "situacion": "enfermedad",
+            "familia_monoparental": false,
+            "numero_hijos": null,
+            "ejercicio": "2025"
+        })).unwrap();
+
+        let call_result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await.unwrap();
+        let text = result_text(&call_result);
+        let response: ExcedenciaResponse = serde_json::from_str(&text).unwrap();
+        let modelo = response.modelo.unwrap();
+        assert_eq!(modelo.ejercicio, 2025);
+        assert!(modelo.version.starts_with("sha256:"));
+    }
+
+    #[tokio::test]
+    async fn test_calculadora_ejercicio_no_disponible() {
+        let calculadora = Calculadora::default();
+        let direct_params = ExcedenciaDirectParams {
+            ejercicio: Some(2019),
+            ..ExcedenciaDirectParams::obligatorios(Parentesco::Madre, Situacion::Enfermedad, false)
+        };
+
+        let call_result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await.unwrap();
+        assert_eq!(call_result.is_error, Some(true));
+        let text = result_text(&call_result);
+        assert!(text.contains("2019") && text.contains("2025"));
+    }
+
+    #[tokio::test]
+    async fn test_engine_reports_all_validation_errors() {
+        let engine = ExcedenciaDecisionEngine::new(ModelRegistry::embedded());
+        let request = serde_json::json!({
+            "input": {
+                "parentesco": "hermano",
+                "situacion": "mudanza",
+                "familia_monoparental": false
+            }
+        });
+
+        let errors = match engine.evaluate_value(request, None, false).await {
+            Err(ExcedenciaError::ValidationError(errors)) => errors,
+            other => panic!("Se esperaban errores de validación: {:?}", other),
+        };
+        assert_eq!(errors.len(), 2);
+
+        let parentesco = errors.iter().find(|e| e.path == "/input/parentesco").unwrap();
+        assert_eq!(parentesco.value, Some(serde_json::json!("hermano")));
+        assert!(parentesco.allowed_values.as_ref().unwrap().contains(&serde_json::json!("pareja")));
+
+        let situacion = errors.iter().find(|e| e.path == "/input/situacion").unwrap();
+        assert_eq!(situacion.value, Some(serde_json::json!("mudanza")));
+        assert!(situacion.allowed_values.as_ref().unwrap().contains(&serde_json::json!("parto")));
+    }
+
+    #[test]
+    fn test_extract_validation_errors_from_node_error() {
+        let source = serde_json::json!({
+            "type": "Validation",
+            "source": {"errors": [
+                {"path": "/input/parentesco", "message": "\"tio\" is not one of [\"padre\"]"},
+                {"path": "/input", "message": "Additional properties are not allowed"}
+            ]}
+        });
+        let error = EvaluationError::NodeError(Box::new(NodeError {
+            node_id: "input-node-001".to_string(),
+            trace: None,
+            source: anyhow::anyhow!(source),
+        }));
+
+        let errors = ExcedenciaDecisionEngine::extract_validation_errors(&error).unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].path, "/input/parentesco");
+        assert_eq!(errors[1].path, "/input");
+
+        let other = EvaluationError::NodeError(Box::new(NodeError {
+            node_id: "decision-table-001".to_string(),
+            trace: None,
+            source: anyhow::anyhow!("fallo en la tabla"),
+        }));
+        assert!(ExcedenciaDecisionEngine::extract_validation_errors(&other).is_none());
+    }
+
+    /// Modificación del contenido JSON del modelo embebido
+    type Modificacion<'a> = &'a dyn Fn(&mut serde_json::Value);
+
+    /// Registro con un modelo por ejercicio, cada uno obtenido modificando el modelo embebido
+    fn registry_modificado(modelos: &[(i32, Modificacion)]) -> ModelRegistry {
+        let dir = tempfile::tempdir().unwrap();
+        for (ejercicio, modificar) in modelos {
+            let mut content: serde_json::Value = serde_json::from_str(crate::common::modelo::MODELO_EMBEBIDO).unwrap();
+            modificar(&mut content);
+            let path = dir.path().join(format!("ayuda-excedencia-{}.json", ejercicio));
+            std::fs::write(&path, content.to_string()).unwrap();
+        }
+        ModelRegistry::load(Some(dir.path()), crate::common::modelo::EJERCICIO_EMBEBIDO).unwrap()
+    }
+
+    /// Cambia el valor de una columna de salida de una regla de la tabla de supuestos
+    fn cambiar_regla(content: &mut serde_json::Value, regla: &str, columna: &str, valor: &str) {
+        for node in content["nodes"].as_array_mut().unwrap() {
+            if node["id"] == "decision-table-001" {
+                for rule in node["content"]["rules"].as_array_mut().unwrap() {
+                    if rule["_id"] == regla {
+                        rule[columna] = serde_json::Value::String(valor.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Registro con un modelo 2025 cuyo schema de entrada no admite `mujer` como parentesco
+    fn registry_sin_mujer() -> ModelRegistry {
+        registry_modificado(&[(2025, &|content| {
+            for node in content["nodes"].as_array_mut().unwrap() {
+                if node["type"] == "inputNode" {
+                    let schema = node["content"]["schema"].as_str().unwrap().replace(r#", "mujer""#, "");
+                    node["content"]["schema"] = serde_json::Value::String(schema);
+                }
+            }
+        })])
+    }
+
+    #[test]
+    fn test_direct_params_normaliza_valores() {
+        let direct_params: ExcedenciaDirectParams = serde_json::from_value(serde_json::json!({
+            "parentesco": "Cónyuge",
+            "situacion": "nacimiento",
+            "familia_monoparental": "true"
+        })).unwrap();
+        assert_eq!(direct_params.parentesco, Parentesco::Conyuge);
+        assert_eq!(direct_params.situacion, Situacion::Parto);
+
+        let error = serde_json::from_value::<ExcedenciaDirectParams>(serde_json::json!({
+            "parentesco": "hermano",
+            "situacion": "parto",
+            "familia_monoparental": false
+        })).unwrap_err();
+        assert!(error.to_string().contains("'hermano'"));
+        assert!(error.to_string().contains("Valores permitidos"));
+    }
+
+    #[tokio::test]
+    async fn test_calculadora_reporta_valores_normalizados() {
+        let calculadora = Calculadora::default();
+        let direct_params: ExcedenciaDirectParams = serde_json::from_value(serde_json::json!({
+            "parentesco": "MADRE",
+            "situacion": "Nacimiento",
+            "familia_monoparental": true,
+            "numero_hijos": 1
+        })).unwrap();
+
+        let call_result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await.unwrap();
+        let response: ExcedenciaResponse = serde_json::from_str(&result_text(&call_result)).unwrap();
+        let input = response.input.unwrap();
+        assert_eq!(input.parentesco, Parentesco::Madre);
+        assert_eq!(input.situacion, Situacion::Parto);
+        assert_eq!(response.output.supuesto, "E");
+    }
+
     #[tokio::test]
     async fn test_calculadora_validation_error() {
-        let calculadora = Calculadora::new();
+        let calculadora = Calculadora::new(registry_sin_mujer());
+        // Mujer no está admitido por el schema de este modelo
         let direct_params = ExcedenciaDirectParams {
-            parentesco: "hermano".to_string(), // No válido
-            situacion: "parto".to_string(),
-            familia_monoparental: false,
-            numero_hijos: None,
+            ejercicio: Some(2025),
+            ..ExcedenciaDirectParams::obligatorios(Parentesco::Mujer, Situacion::Parto, false)
         };
         
         let result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await;
         match result {
-            Ok(call_result) => {
-                // Should handle validation errors appropriately
-                println!("Validation result: {:?}", call_result);
+            Err(e) => {
+                assert_eq!(e.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+                assert!(e.message.contains("/input/parentesco"));
+                let errores = &e.data.expect("el error debe incluir los detalles")["errores"];
+                assert_eq!(errores[0]["value"], serde_json::json!("mujer"));
             },
-            Err(e) => panic!("Error inesperado: {}", e),
+            Ok(call_result) => panic!("Se esperaba un error invalid_params: {:?}", call_result),
         }
     }
+
+    #[tokio::test]
+    async fn test_metricas_de_evaluacion_y_validacion() {
+        let metricas = Metricas::new();
+        let calculadora = Calculadora::default().with_metricas(metricas.clone());
+
+        let arguments = |parentesco: &str| serde_json::json!({
+            "parentesco": parentesco, "situacion": "enfermedad", "familia_monoparental": false,
+            "hospitalizacion": true, "cuidado_continuado": true
+        });
+        let params = |parentesco: &str| -> ExcedenciaDirectParams { serde_json::from_value(arguments(parentesco)).unwrap() };
+        calculadora.evaluar_supuesto_excedencia(Parameters(params("madre"))).await.unwrap();
+        calculadora.evaluar_supuestos_aplicables(Parameters(params("madre"))).await.unwrap();
+
+        // Un valor sin alias falla al deserializar los parámetros, antes de llegar al motor
+        let error = calculadora.validar_valores(arguments("hermano").as_object()).unwrap_err();
+        assert_eq!(error.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert_eq!(error.data.unwrap()["errores"][0]["path"], "/input/parentesco");
+        assert!(calculadora.validar_valores(arguments("Madre").as_object()).is_ok());
+
+        // Un valor que el modelo no admite se rechaza al validar con su schema de entrada
+        let sin_mujer = Calculadora::new(registry_sin_mujer()).with_metricas(metricas.clone());
+        sin_mujer.evaluar_supuesto_excedencia(Parameters(params("mujer"))).await.unwrap_err();
+
+        let texto = metricas.texto();
+        assert!(texto.contains(r#"bon_evaluaciones_total{supuesto="A",tiene_derecho_potencial="true"} 2"#));
+        assert!(texto.contains(r#"bon_errores_validacion_total{campo="parentesco"} 2"#));
+        assert!(texto.contains("bon_evaluacion_duracion_segundos_count 2"));
+    }
+
+    #[tokio::test]
+    async fn test_evaluar_supuestos_aplicables() {
+        let calculadora = Calculadora::default();
+        let direct_params = ExcedenciaDirectParams {
+            numero_hijos: Some(3.0),
+            ..ExcedenciaDirectParams::obligatorios(Parentesco::Madre, Situacion::Parto, true)
+        };
+
+        let call_result = calculadora.evaluar_supuestos_aplicables(Parameters(direct_params)).await.unwrap();
+        let aplicables: SupuestosAplicables = serde_json::from_str(&result_text(&call_result)).unwrap();
+        let supuestos: Vec<&str> = aplicables.supuestos.iter().map(|s| s.supuesto.as_str()).collect();
+        assert_eq!(supuestos, ["E", "B"]);
+        assert_eq!(aplicables.supuestos[1].regla, "regla-002-sin-edades");
+        assert_eq!(aplicables.supuestos[1].importe_mensual, 500);
+        assert_eq!(aplicables.supuesto_prelacion.as_deref(), Some("E"));
+        assert_eq!(aplicables.supuesto_mas_favorable.as_deref(), Some("E"));
+        assert_eq!(aplicables.modelo.ejercicio, 2025);
+    }
+
+    #[tokio::test]
+    async fn test_evaluar_supuestos_sin_supuesto_aplicable() {
+        let calculadora = Calculadora::default();
+        let direct_params = ExcedenciaDirectParams {
+            numero_hijos: Some(1.0),
+            ..ExcedenciaDirectParams::obligatorios(Parentesco::Madre, Situacion::Parto, false)
+        };
+
+        let call_result = calculadora.evaluar_supuestos_aplicables(Parameters(direct_params)).await.unwrap();
+        let aplicables: SupuestosAplicables = serde_json::from_str(&result_text(&call_result)).unwrap();
+        assert!(aplicables.supuestos.is_empty());
+        assert_eq!(aplicables.supuesto_prelacion, None);
+        assert_eq!(aplicables.supuesto_mas_favorable, None);
+    }
+
+    #[tokio::test]
+    async fn test_evaluar_supuestos_b_denegado() {
+        // Solo un hijo menor de 6 años: la primera regla que se cumple deniega el B
+        let hijos = serde_json::json!([
+            {"fecha_nacimiento": "2025-03-01"},
+            {"fecha_nacimiento": "2019-03-01"},
+            {"fecha_nacimiento": "2017-01-10"}
+        ]);
+        let params = serde_json::json!({"parentesco": "madre", "situacion": "parto", "familia_monoparental": false, "hijos": hijos});
+        let output = evaluar(params.clone()).await;
+        assert_eq!(output.supuesto, "B");
+        assert!(!output.tiene_derecho_potencial);
+
+        let direct_params: ExcedenciaDirectParams = serde_json::from_value(params).unwrap();
+        let call_result = Calculadora::default().evaluar_supuestos_aplicables(Parameters(direct_params)).await.unwrap();
+        let aplicables: SupuestosAplicables = serde_json::from_str(&result_text(&call_result)).unwrap();
+        assert!(aplicables.supuestos.is_empty());
+        assert_eq!(aplicables.supuesto_prelacion, None);
+        assert_eq!(aplicables.supuesto_mas_favorable, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_evaluar_lote() {
+        let calculadora = Calculadora::default();
+        let mut casos = vec![
+            serde_json::json!({"id": "E-001", "parentesco": "madre", "situacion": "enfermedad", "familia_monoparental": false}),
+            serde_json::json!({"id": "E-002", "parentesco": "hermano", "situacion": "parto", "familia_monoparental": false}),
+            serde_json::json!({"id": "E-003", "parentesco": "padre", "situacion": "parto", "familia_monoparental": false, "numero_hijos": 3}),
+            serde_json::json!({"id": "E-004", "parentesco": "madre", "situacion": "parto", "familia_monoparental": false, "ejercicio": 2019}),
+        ];
+        casos.extend((5..=40).map(|i| serde_json::json!({
+            "id": format!("E-{:03}", i), "parentesco": "Madre", "situacion": "adopción", "familia_monoparental": false
+        })));
+        let params: EvaluarLoteParams = serde_json::from_value(serde_json::json!({ "casos": casos })).unwrap();
+
+        let call_result = calculadora.evaluar_lote(Parameters(params)).await.unwrap();
+        let lote: ResultadoLote = serde_json::from_str(&result_text(&call_result)).unwrap();
+
+        let ids: Vec<&str> = lote.resultados.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids.len(), 40);
+        assert_eq!(&ids[..4], ["E-001", "E-002", "E-003", "E-004"]);
+        assert_eq!(lote.resultados[0].resultado.as_ref().unwrap().output.supuesto, "A");
+        assert!(lote.resultados[1].error.as_ref().unwrap().contains("hermano"));
+        assert_eq!(lote.resultados[1].errores_validacion[0].path, "/input/parentesco");
+        assert!(lote.resultados[3].error.as_ref().unwrap().contains("2019"));
+
+        assert_eq!(lote.resumen.total, 40);
+        assert_eq!(lote.resumen.evaluados, 38);
+        assert_eq!(lote.resumen.con_error, 2);
+        assert_eq!(lote.resumen.por_supuesto["A"], 1);
+        assert_eq!(lote.resumen.por_supuesto["B"], 1);
+        assert_eq!(lote.resumen.por_supuesto["C"], 36);
+        assert_eq!(lote.resumen.importe_mensual_total, 725 + 500 + 36 * 500);
+    }
+
+    #[tokio::test]
+    async fn test_evaluar_lote_demasiados_casos() {
+        let calculadora = Calculadora::default();
+        let casos: Vec<_> = (0..=MAX_CASOS_LOTE).map(|i| serde_json::json!({"id": i})).collect();
+        let params: EvaluarLoteParams = serde_json::from_value(serde_json::json!({ "casos": casos })).unwrap();
+
+        let error = calculadora.evaluar_lote(Parameters(params)).await.unwrap_err();
+        assert_eq!(error.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+
+        // El schema anuncia el mismo límite y todos los parámetros de cada caso
+        let tool = calculadora.list_all_tools().into_iter().find(|tool| tool.name == "evaluar_lote").unwrap();
+        assert_eq!(tool.input_schema["properties"]["casos"]["maxItems"], MAX_CASOS_LOTE);
+        let schema = serde_json::to_string(&tool.input_schema).unwrap();
+        assert!(["hijos", "hospitalizacion", "duracion_acogimiento_meses"].iter().all(|campo| schema.contains(campo)));
+    }
+
+    fn params_periodo(fecha_inicio: &str, fecha_fin: &str) -> ImportePeriodoParams {
+        serde_json::from_value(serde_json::json!({
+            "parentesco": "madre",
+            "situacion": "enfermedad",
+            "familia_monoparental": false,
+            "hospitalizacion": true,
+            "cuidado_continuado": true,
+            "fecha_inicio": fecha_inicio,
+            "fecha_fin": fecha_fin
+        })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_calcular_importe_periodo() {
+        let calculadora = Calculadora::default();
+        let params = params_periodo("2025-03-15", "2026-01-31");
+
+        let call_result = calculadora.calcular_importe_periodo(Parameters(params)).await.unwrap();
+        let periodo: ImportePeriodo = serde_json::from_str(&result_text(&call_result)).unwrap();
+
+        assert_eq!(periodo.meses.len(), 11);
+        assert_eq!(periodo.meses[0].mes, "2025-03");
+        assert_eq!((periodo.meses[0].dias, periodo.meses[0].dias_mes), (17, 31));
+        assert_eq!(periodo.meses[0].importe, 397.58);
+        assert!(periodo.meses[1..].iter().all(|mes| mes.importe == 725.0));
+        assert_eq!(periodo.importe_total, 397.58 + 10.0 * 725.0);
+
+        // Sin modelo 2026 se aplica la última normativa anterior
+        let ejercicios: Vec<i32> = periodo.tramos.iter().map(|tramo| tramo.ejercicio).collect();
+        assert_eq!(ejercicios, [2025, 2026]);
+        assert_eq!(periodo.tramos[1].modelo.ejercicio, 2025);
+        assert_eq!(periodo.tramos[0].importe, 397.58 + 9.0 * 725.0);
+        // El modelo incluido no define duraciones máximas: el periodo no se limita y se advierte
+        assert_eq!(periodo.duracion_maxima_meses, None);
+        assert_eq!(periodo.fecha_fin_bonificable, periodo.fecha_fin);
+        assert!(periodo.advertencias[0].contains("no define una duración máxima"));
+        assert!(periodo.advertencias.iter().any(|a| a == "No hay modelo de decisión para 2026: se aplica la normativa de 2025"));
+    }
+
+    #[tokio::test]
+    async fn test_calcular_importe_periodo_duracion_maxima_y_ejercicios() {
+        let calculadora = Calculadora::new(registry_modificado(&[
+            (2025, &|content| cambiar_regla(content, "regla-001", "output-duracion-maxima-meses", "12")),
+            (2026, &|content| {
+                cambiar_regla(content, "regla-001", "output-duracion-maxima-meses", "12");
+                cambiar_regla(content, "regla-001", "output-importe-mensual", "800");
+            }),
+        ]));
+        let params = params_periodo("2025-03-15", "2026-06-30");
+
+        let call_result = calculadora.calcular_importe_periodo(Parameters(params)).await.unwrap();
+        let periodo: ImportePeriodo = serde_json::from_str(&result_text(&call_result)).unwrap();
+
+        assert_eq!(periodo.duracion_maxima_meses, Some(12));
+        assert_eq!(periodo.fecha_fin_bonificable, "2026-03-14".parse::<NaiveDate>().unwrap());
+        let ultimo_bonificable = periodo.meses.iter().position(|mes| mes.hasta == periodo.fecha_fin_bonificable).unwrap();
+        let ultimo = &periodo.meses[ultimo_bonificable];
+        assert_eq!((ultimo.mes.as_str(), ultimo.dias, ultimo.importe_mensual), ("2026-03", 14, 800));
+        assert_eq!(ultimo.importe, 361.29);
+
+        // El resto de marzo y de abril a junio superan la duración máxima: 0€
+        let fuera: Vec<(&str, u32, f64)> = periodo.meses[ultimo_bonificable + 1..]
+            .iter()
+            .map(|mes| (mes.mes.as_str(), mes.dias, mes.importe))
+            .collect();
+        assert_eq!(fuera, [("2026-03", 17, 0.0), ("2026-04", 30, 0.0), ("2026-05", 31, 0.0), ("2026-06", 30, 0.0)]);
+        assert_eq!(periodo.meses.last().unwrap().hasta, periodo.fecha_fin);
+
+        assert_eq!(periodo.tramos.len(), 2);
+        assert_eq!(periodo.tramos[1].modelo.ejercicio, 2026);
+        assert_eq!(periodo.tramos[1].importe, 2.0 * 800.0 + 361.29);
+        assert_eq!(periodo.tramos[1].hasta, periodo.fecha_fin_bonificable);
+        assert_eq!(periodo.importe_total, 397.58 + 9.0 * 725.0 + 2.0 * 800.0 + 361.29);
+        assert!(periodo.advertencias.iter().any(|a| a.contains("12 meses")));
+        assert!(!periodo.advertencias.iter().any(|a| a.contains("No hay modelo de decisión")));
+    }
+
+    #[tokio::test]
+    async fn test_calcular_importe_periodo_fechas_invertidas() {
+        let calculadora = Calculadora::default();
+        let params = params_periodo("2025-03-15", "2025-01-31");
+
+        let error = calculadora.calcular_importe_periodo(Parameters(params)).await.unwrap_err();
+        assert_eq!(error.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_explicar_evaluacion() {
+        let calculadora = Calculadora::default();
+        let direct_params = ExcedenciaDirectParams {
+            numero_hijos: Some(3.0),
+            ..ExcedenciaDirectParams::obligatorios(Parentesco::Madre, Situacion::Parto, true)
+        };
+
+        let call_result = calculadora.explicar_evaluacion(Parameters(direct_params)).await.unwrap();
+        let explicacion: ExplicacionEvaluacion = serde_json::from_str(&result_text(&call_result)).unwrap();
+        assert_eq!(explicacion.resultado.output.supuesto, "E");
+
+        let traza = explicacion.traza;
+        let regla = traza.regla_aplicada.unwrap();
+        assert_eq!(regla.id, "regla-005");
+        assert!(regla.descripcion.starts_with("Supuesto E"));
+        assert_eq!(regla.valores["input.numero_hijos"], serde_json::json!(3));
+        assert_eq!(traza.parentesco_valido, Some(true));
+
+        let nodos: Vec<&str> = traza.nodos.iter().map(|nodo| nodo.nombre.as_str()).collect();
+        assert_eq!(nodos, ["solicitud", "eval_parentesco", "eval_hijos", "eval_hospitalizacion", "eval_acogimiento", "eval_potencial_ayuda_excedencia", "resolucion"]);
+        assert!(traza.nodos.iter().all(|nodo| nodo.duracion.is_some()));
+    }
+
+    #[test]
+    fn test_traza_no_disponible() {
+        let result = DecisionGraphResponse { performance: String::new(), result: serde_json::json!({}).into(), trace: None };
+        let error = ExcedenciaDecisionEngine::excedencia_response(&DecisionModel::embedded(), result, true).unwrap_err();
+        assert!(matches!(error, ExcedenciaError::TrazaNoDisponibleError));
+    }
+
+    #[test]
+    fn test_tool_schema_includes_model_enums() {
+        let calculadora = Calculadora::default();
+        let tools = calculadora.list_all_tools();
+        let tool = tools.iter().find(|t| t.name == "evaluar_supuesto_excedencia").unwrap();
+        let schema = serde_json::Value::Object(tool.input_schema.as_ref().clone());
+
+        let parentesco = schema.pointer("/properties/parentesco/enum").unwrap().as_array().unwrap();
+        assert!(parentesco.contains(&serde_json::json!("madre")));
+        let situacion = schema.pointer("/properties/situacion/enum").unwrap().as_array().unwrap();
+        assert!(situacion.contains(&serde_json::json!("parto_multiple")));
+
+        // Solo se anuncian los valores que admiten a la vez el enum de Rust y el modelo
+        let tools = Calculadora::new(registry_sin_mujer()).list_all_tools();
+        let tool = tools.iter().find(|t| t.name == "evaluar_supuesto_excedencia").unwrap();
+        let schema = serde_json::Value::Object(tool.input_schema.as_ref().clone());
+        let parentesco = schema.pointer("/properties/parentesco/enum").unwrap().as_array().unwrap();
+        assert!(!parentesco.contains(&serde_json::json!("mujer")));
+        assert!(parentesco.contains(&serde_json::json!("marido")));
+    }
+
+    #[tokio::test]
+    async fn test_decision_del_manifiesto_como_herramienta() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ayuda.json"), crate::common::modelo::MODELO_EMBEBIDO).unwrap();
+        let manifest = dir.path().join("decisiones.yaml");
+        std::fs::write(&manifest, "decision:\n  - name: ayuda_generica\n    description: Ayuda por excedencia\n    path: ayuda.json\n").unwrap();
+        let decisiones = DecisionRegistry::load(&manifest, &Calculadora::tool_names()).unwrap();
+
+        let metricas = Metricas::new();
+        let calculadora = Calculadora::default().with_decisiones(decisiones.clone()).with_metricas(metricas.clone());
+        assert!(calculadora.list_all_tools().iter().any(|tool| tool.name == "ayuda_generica"));
+
+        let herramienta = decisiones.get("ayuda_generica").unwrap();
+        let arguments = serde_json::json!({
+            "input": { "parentesco": "padre", "situacion": "parto", "familia_monoparental": true, "numero_hijos": 1 }
+        });
+        let result = calculadora.call_decision(herramienta.clone(), arguments.as_object().cloned()).await.unwrap();
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.structured_content.unwrap()["output"]["supuesto"], "E");
+        let texto = metricas.texto();
+        assert!(texto.contains(r#"bon_evaluaciones_decision_total{herramienta="ayuda_generica",resultado="ok"} 1"#));
+        assert!(texto.contains("bon_evaluacion_duracion_segundos_count 1"));
+
+        let arguments = serde_json::json!({ "input": { "parentesco": "vecino", "situacion": "parto", "familia_monoparental": true } });
+        let error = calculadora.call_decision(herramienta, arguments.as_object().cloned()).await.unwrap_err();
+        assert_eq!(error.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_contenido_estructurado_con_output_schema() {
+        let calculadora = Calculadora::default();
+        let tools = calculadora.list_all_tools();
+        let evaluar = tools.iter().find(|tool| tool.name == "evaluar_supuesto_excedencia").unwrap();
+        let output_schema = evaluar.output_schema.as_ref().expect("evaluar_supuesto_excedencia debe declarar output_schema");
+        assert_eq!(output_schema.get("type"), Some(&serde_json::json!("object")));
+        assert!(tools.iter().all(|tool| tool.output_schema.is_some()));
+
+        let direct_params: ExcedenciaDirectParams = serde_json::from_value(serde_json::json!({
+            "parentesco": "madre", "situacion": "parto", "familia_monoparental": true, "numero_hijos": 1
+        })).unwrap();
+        let call_result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await.unwrap();
+        let structured = call_result.structured_content.clone().expect("el resultado debe ir como contenido estructurado");
+        assert_eq!(structured["output"]["supuesto"], "E");
+        assert!(structured["output"]["importe_mensual"].is_number());
+        // El texto sigue siendo el mismo JSON, para los clientes que no leen structuredContent
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&result_text(&call_result)).unwrap(), structured);
+
+        // Los errores de evaluación van solo como texto
+        let direct_params: ExcedenciaDirectParams = serde_json::from_value(serde_json::json!({
+            "parentesco": "madre", "situacion": "parto", "familia_monoparental": true, "ejercicio": 1999
+        })).unwrap();
+        let call_result = calculadora.evaluar_supuesto_excedencia(Parameters(direct_params)).await.unwrap();
+        assert_eq!(call_result.is_error, Some(true));
+        assert!(call_result.structured_content.is_none());
+    }
+
+    #[test]
+    fn test_permiso_insuficiente() {
+        let portal = Identidad { nombre: "portal".to_string(), permisos: [Permiso::Evaluar].into() };
+        assert!(permiso_insuficiente(&portal, "evaluar_supuesto_excedencia").is_none());
+
+        let resultado = permiso_insuficiente(&portal, "evaluar_lote").unwrap();
+        assert_eq!(resultado.is_error, Some(true));
+        assert!(resultado.structured_content.is_none());
+        assert_eq!(result_text(&resultado), "Permiso insuficiente: El cliente 'portal' no tiene el permiso 'lote'");
+    }
 }
\ No newline at end of file
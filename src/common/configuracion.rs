@@ -0,0 +1,422 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use super::autenticacion::AuthArgs;
+use super::modelo::{DECISION_MODEL_RELOAD_SECS_POR_DEFECTO, ModelArgs};
+use super::transporte::HttpArgs;
+
+/// Variable de entorno con la ruta del fichero de configuración
+pub const CONFIG_FILE_ENV: &str = "CONFIG_FILE";
+
+/// Variable de entorno con la dirección en la que escuchan los servidores HTTP
+pub const HOST_ENV: &str = "HOST";
+
+/// Variable de entorno con el puerto de los servidores HTTP
+pub const PORT_ENV: &str = "PORT";
+
+/// Variable de entorno con la dirección completa `host:puerto` (HOST y PORT tienen prioridad)
+pub const BIND_ADDRESS_ENV: &str = "BIND_ADDRESS";
+
+/// Variable de entorno con el nivel de log, con la sintaxis de `tracing_subscriber::EnvFilter`
+pub const LOG_ENV: &str = "RUST_LOG";
+
+/// Variable de entorno con la ruta del stream SSE
+pub const SSE_PATH_ENV: &str = "SSE_PATH";
+
+/// Variable de entorno con la ruta en la que el cliente SSE envía sus mensajes
+pub const SSE_POST_PATH_ENV: &str = "SSE_POST_PATH";
+
+/// Variable de entorno con el intervalo de keep-alive de los streams SSE
+pub const SSE_KEEP_ALIVE_SECS_ENV: &str = "SSE_KEEP_ALIVE_SECS";
+
+pub const HOST_POR_DEFECTO: &str = "127.0.0.1";
+pub const PORT_POR_DEFECTO: u16 = 8001;
+pub const LOG_POR_DEFECTO: &str = "debug";
+pub const SSE_PATH_POR_DEFECTO: &str = "/sse";
+pub const SSE_POST_PATH_POR_DEFECTO: &str = "/message";
+pub const SSE_KEEP_ALIVE_SECS_POR_DEFECTO: u64 = 15;
+
+// =================== ERRORES ===================
+
+#[derive(Debug)]
+pub enum ConfiguracionError {
+    Lectura { path: PathBuf, source: std::io::Error },
+    Formato { path: PathBuf, message: String },
+    Valor { campo: &'static str, message: String },
+}
+
+impl fmt::Display for ConfiguracionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfiguracionError::Lectura { path, source } => {
+                write!(f, "No se puede leer el fichero de configuración '{}': {}", path.display(), source)
+            },
+            ConfiguracionError::Formato { path, message } => {
+                write!(f, "El fichero de configuración '{}' no es válido: {}", path.display(), message)
+            },
+            ConfiguracionError::Valor { campo, message } => {
+                write!(f, "Valor de configuración no válido en '{}': {}", campo, message)
+            },
+        }
+    }
+}
+
+impl std::error::Error for ConfiguracionError {}
+
+// =================== SECCIONES ===================
+
+/// Dirección de escucha y log. Es la sección `[servidor]` de la configuración
+#[derive(Debug, Clone, Default, PartialEq, clap::Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServidorArgs {
+    /// Dirección en la que escuchan los servidores HTTP (0.0.0.0 en contenedores) [env: HOST] [por defecto: 127.0.0.1]
+    #[arg(long = "host", value_name = "HOST")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+
+    /// Puerto de los servidores HTTP [env: PORT] [por defecto: 8001]
+    #[arg(long = "port", value_name = "PUERTO")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+
+    /// Dirección completa host:puerto. --host y --port tienen prioridad, y cualquiera de
+    /// los tres argumentos la tiene sobre HOST, PORT y BIND_ADDRESS [env: BIND_ADDRESS]
+    #[arg(long = "bind-address", value_name = "HOST:PUERTO")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<String>,
+
+    /// Nivel de log, p. ej. 'info' o 'info,zen_engine=warn' [por defecto: debug]
+    #[arg(long = "log-level", env = LOG_ENV, value_name = "FILTRO")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>,
+}
+
+impl ServidorArgs {
+    /// Capa de las variables HOST, PORT y BIND_ADDRESS. No las lee clap porque mezclaría
+    /// variables y argumentos en una sola capa, y HOST podría pisar a --bind-address
+    fn from_env(var: impl Fn(&str) -> Option<String>) -> Result<Self, ConfiguracionError> {
+        let port = var(PORT_ENV)
+            .map(|port| {
+                port.parse().map_err(|_| ConfiguracionError::Valor {
+                    campo: "port",
+                    message: format!("'{}' no es un puerto válido", port),
+                })
+            })
+            .transpose()?;
+        let mut servidor = Self { host: var(HOST_ENV), port, bind_address: var(BIND_ADDRESS_ENV), log: None };
+        servidor.normalizar()?;
+        Ok(servidor)
+    }
+
+    /// Sustituye `bind_address` por `host` y `port`, sin pisar los que ya estén indicados,
+    /// para que cada capa de configuración se combine campo a campo
+    fn normalizar(&mut self) -> Result<(), ConfiguracionError> {
+        let Some(bind_address) = self.bind_address.take() else {
+            return Ok(());
+        };
+        let no_valida = || ConfiguracionError::Valor {
+            campo: "bind_address",
+            message: format!("'{}' no tiene la forma host:puerto", bind_address),
+        };
+        let (host, port) = bind_address.rsplit_once(':').ok_or_else(no_valida)?;
+        let port = port.parse().map_err(|_| no_valida())?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        self.host.get_or_insert_with(|| host.to_string());
+        self.port.get_or_insert(port);
+        Ok(())
+    }
+
+    /// Dirección `host:puerto` en la que escuchar
+    pub fn bind_address(&self) -> String {
+        let host = self.host.as_deref().unwrap_or(HOST_POR_DEFECTO);
+        let port = self.port.unwrap_or(PORT_POR_DEFECTO);
+        if host.contains(':') { format!("[{}]:{}", host, port) } else { format!("{}:{}", host, port) }
+    }
+
+    pub fn log(&self) -> &str {
+        self.log.as_deref().unwrap_or(LOG_POR_DEFECTO)
+    }
+}
+
+/// Rutas y keep-alive del transporte SSE. Es la sección `[sse]` de la configuración
+#[derive(Debug, Clone, Default, PartialEq, clap::Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SseArgs {
+    /// Ruta del stream SSE [por defecto: /sse]
+    #[arg(long = "sse-path", env = SSE_PATH_ENV, value_name = "RUTA")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sse_path: Option<String>,
+
+    /// Ruta en la que el cliente SSE envía sus mensajes [por defecto: /message]
+    #[arg(long = "sse-post-path", env = SSE_POST_PATH_ENV, value_name = "RUTA")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_path: Option<String>,
+
+    /// Intervalo en segundos de los keep-alive de los streams SSE y streamable HTTP [por defecto: 15]
+    #[arg(long = "sse-keep-alive-secs", env = SSE_KEEP_ALIVE_SECS_ENV, value_name = "SECS")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive_secs: Option<u64>,
+}
+
+impl SseArgs {
+    pub fn sse_path(&self) -> String {
+        self.sse_path.clone().unwrap_or_else(|| SSE_PATH_POR_DEFECTO.to_string())
+    }
+
+    pub fn post_path(&self) -> String {
+        self.post_path.clone().unwrap_or_else(|| SSE_POST_PATH_POR_DEFECTO.to_string())
+    }
+
+    pub fn keep_alive(&self) -> Duration {
+        Duration::from_secs(self.keep_alive_secs.unwrap_or(SSE_KEEP_ALIVE_SECS_POR_DEFECTO))
+    }
+}
+
+// =================== CONFIGURACIÓN ===================
+
+/// Configuración común a todos los binarios. Cada valor se toma, de menor a mayor
+/// prioridad, del valor por defecto, del fichero de configuración, de la variable de
+/// entorno y del argumento de línea de comandos
+#[derive(Debug, Clone, Default, PartialEq, clap::Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Configuracion {
+    #[command(flatten)]
+    pub servidor: ServidorArgs,
+
+    #[command(flatten)]
+    pub sse: SseArgs,
+
+    #[command(flatten)]
+    #[serde(rename = "modelo")]
+    pub model: ModelArgs,
+
+    #[command(flatten)]
+    pub auth: AuthArgs,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
+}
+
+impl Configuracion {
+    /// Valores por defecto explícitos, para que `--print-config` muestre la configuración completa
+    pub fn por_defecto() -> Self {
+        let mut config = Self::default();
+        config.servidor.host = Some(HOST_POR_DEFECTO.to_string());
+        config.servidor.port = Some(PORT_POR_DEFECTO);
+        config.servidor.log = Some(LOG_POR_DEFECTO.to_string());
+        config.sse.sse_path = Some(SSE_PATH_POR_DEFECTO.to_string());
+        config.sse.post_path = Some(SSE_POST_PATH_POR_DEFECTO.to_string());
+        config.sse.keep_alive_secs = Some(SSE_KEEP_ALIVE_SECS_POR_DEFECTO);
+        config.model.decision_model_reload_secs = Some(DECISION_MODEL_RELOAD_SECS_POR_DEFECTO);
+        config
+    }
+
+    /// Lee un fichero de configuración TOML o YAML, según su extensión
+    pub fn from_file(path: &Path) -> Result<Self, ConfiguracionError> {
+        let contenido = std::fs::read_to_string(path)
+            .map_err(|source| ConfiguracionError::Lectura { path: path.to_path_buf(), source })?;
+        let formato = |message: String| ConfiguracionError::Formato { path: path.to_path_buf(), message };
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_str(&contenido).map_err(|e| formato(e.to_string())),
+            Some("toml") => toml::from_str(&contenido).map_err(|e| formato(e.to_string())),
+            _ => Err(formato("la extensión debe ser .toml, .yaml o .yml".to_string())),
+        }
+    }
+
+    /// Combina dos capas: los valores indicados en `encima` sustituyen a los de `self`
+    pub fn con(self, encima: Self) -> Result<Self, ConfiguracionError> {
+        combinar(&self, &encima)
+    }
+
+    /// Comprueba los valores que no puede comprobar el tipo de cada campo
+    pub fn validar(&self) -> Result<(), ConfiguracionError> {
+        for (campo, ruta) in [("sse.sse_path", &self.sse.sse_path), ("sse.post_path", &self.sse.post_path)] {
+            if let Some(ruta) = ruta
+                && !ruta.starts_with('/')
+            {
+                return Err(ConfiguracionError::Valor { campo, message: format!("'{}' debe empezar por '/'", ruta) });
+            }
+        }
+        if self.sse.keep_alive_secs == Some(0) {
+            return Err(ConfiguracionError::Valor { campo: "sse.keep_alive_secs", message: "debe ser mayor que 0".to_string() });
+        }
+        Ok(())
+    }
+
+    /// Configuración efectiva en TOML, tal y como la muestra `--print-config`
+    pub fn to_toml(&self) -> Result<String, ConfiguracionError> {
+        toml::to_string_pretty(self)
+            .map_err(|e| ConfiguracionError::Valor { campo: "configuración", message: e.to_string() })
+    }
+}
+
+/// Combina dos valores serializables campo a campo: los campos presentes en `encima`
+/// sustituyen a los de `base`. Los campos `None` no se serializan, así que no pisan nada
+fn combinar<T: Serialize + DeserializeOwned>(base: &T, encima: &T) -> Result<T, ConfiguracionError> {
+    fn fusionar(base: &mut serde_json::Value, encima: serde_json::Value) {
+        match (base, encima) {
+            (serde_json::Value::Object(base), serde_json::Value::Object(encima)) => {
+                for (clave, valor) in encima {
+                    match base.get_mut(&clave) {
+                        Some(actual) => fusionar(actual, valor),
+                        None => {
+                            base.insert(clave, valor);
+                        },
+                    }
+                }
+            },
+            (base, encima) => *base = encima,
+        }
+    }
+
+    let error = |e: serde_json::Error| ConfiguracionError::Valor { campo: "configuración", message: e.to_string() };
+    let mut valor = serde_json::to_value(base).map_err(error)?;
+    fusionar(&mut valor, serde_json::to_value(encima).map_err(error)?);
+    serde_json::from_value(valor).map_err(error)
+}
+
+// =================== ARGUMENTOS CLI ===================
+
+/// Argumentos de configuración de todos los binarios
+#[derive(Debug, Clone, clap::Args)]
+pub struct ConfigArgs {
+    /// Fichero de configuración TOML o YAML. Las variables de entorno y los argumentos tienen prioridad sobre él
+    #[arg(long = "config", env = CONFIG_FILE_ENV, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Muestra la configuración efectiva en TOML y termina
+    #[arg(long = "print-config")]
+    pub print_config: bool,
+
+    #[command(flatten)]
+    pub valores: Configuracion,
+}
+
+impl ConfigArgs {
+    /// Configuración efectiva: valores por defecto, fichero, variables de entorno y argumentos
+    pub fn load(&self) -> Result<Configuracion, ConfiguracionError> {
+        self.load_con_entorno(|nombre| std::env::var(nombre).ok())
+    }
+
+    /// Como [`ConfigArgs::load`], leyendo HOST, PORT y BIND_ADDRESS con `var`
+    fn load_con_entorno(&self, var: impl Fn(&str) -> Option<String>) -> Result<Configuracion, ConfiguracionError> {
+        let mut fichero = match &self.config {
+            Some(path) => Configuracion::from_file(path)?,
+            None => Configuracion::default(),
+        };
+        fichero.servidor.normalizar()?;
+        let entorno = Configuracion { servidor: ServidorArgs::from_env(var)?, ..Configuracion::default() };
+        // Para el resto de valores clap ya resuelve la prioridad entre variable de entorno y argumento
+        let mut argumentos = self.valores.clone();
+        argumentos.servidor.normalizar()?;
+
+        let config = Configuracion::por_defecto().con(fichero)?.con(entorno)?.con(argumentos)?;
+        config.validar()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches, Parser};
+
+    #[derive(Debug, Parser)]
+    struct Args {
+        #[command(flatten)]
+        config: ConfigArgs,
+    }
+
+    /// Analiza los argumentos sin leer variables de entorno, para que los tests no dependan
+    /// de lo que haya exportado quien los ejecuta
+    fn parse_sin_entorno(argumentos: &[&str]) -> ConfigArgs {
+        let matches = Args::command().mut_args(|arg| arg.env(None)).get_matches_from(argumentos);
+        Args::from_arg_matches(&matches).unwrap().config
+    }
+
+    /// Fichero de configuración en un directorio temporal, que se borra al soltar el `TempDir`
+    fn fichero(nombre: &str, contenido: &str) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(nombre);
+        std::fs::write(&path, contenido).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_prioridad_fichero_y_argumentos() {
+        let (_dir, path) = fichero("bon.toml", r#"
+            [servidor]
+            bind_address = "0.0.0.0:9000"
+            log = "info"
+
+            [sse]
+            keep_alive_secs = 30
+
+            [modelo]
+            path = "/app/modelos"
+        "#);
+
+        let args = parse_sin_entorno(&["test", "--config", path.to_str().unwrap(), "--port", "9100", "--sse-path", "/eventos"]);
+        let config = args.load_con_entorno(|_| None).unwrap();
+        assert_eq!(config.servidor.bind_address(), "0.0.0.0:9100");
+        assert_eq!(config.servidor.log(), "info");
+        assert_eq!(config.sse.sse_path(), "/eventos");
+        assert_eq!(config.sse.post_path(), SSE_POST_PATH_POR_DEFECTO);
+        assert_eq!(config.sse.keep_alive(), Duration::from_secs(30));
+        assert_eq!(config.model.decision_model_path, Some(PathBuf::from("/app/modelos")));
+        assert_eq!(config.model.decision_model_reload_secs, Some(DECISION_MODEL_RELOAD_SECS_POR_DEFECTO));
+
+        let impresa = config.to_toml().unwrap();
+        assert!(impresa.contains("port = 9100"));
+        assert!(impresa.contains("[modelo]"));
+        assert_eq!(toml::from_str::<Configuracion>(&impresa).unwrap(), config);
+    }
+
+    #[test]
+    fn test_fichero_yaml_y_errores() {
+        let (_dir, path) = fichero("bon.yaml", "servidor:\n  host: '::1'\nauth:\n  jwks_file: /etc/bon/jwks.json\n");
+        let config = parse_sin_entorno(&["test", "--config", path.to_str().unwrap()]).load_con_entorno(|_| None).unwrap();
+        assert_eq!(config.servidor.bind_address(), "[::1]:8001");
+        assert_eq!(config.auth.jwks_file, Some(PathBuf::from("/etc/bon/jwks.json")));
+
+        std::fs::write(&path, "servidor:\n  puerto: 9000\n").unwrap();
+        let error = parse_sin_entorno(&["test", "--config", path.to_str().unwrap()]).load_con_entorno(|_| None).unwrap_err();
+        assert!(matches!(error, ConfiguracionError::Formato { .. }));
+
+        let error = parse_sin_entorno(&["test", "--sse-post-path", "message"]).load_con_entorno(|_| None).unwrap_err();
+        assert!(matches!(error, ConfiguracionError::Valor { campo: "sse.post_path", .. }));
+
+        let error = parse_sin_entorno(&["test", "--bind-address", "localhost"]).load_con_entorno(|_| None).unwrap_err();
+        assert!(matches!(error, ConfiguracionError::Valor { campo: "bind_address", .. }));
+
+        let error = parse_sin_entorno(&["test"]).load_con_entorno(|nombre| (nombre == PORT_ENV).then(|| "http".to_string())).unwrap_err();
+        assert!(matches!(error, ConfiguracionError::Valor { campo: "port", .. }));
+    }
+
+    #[test]
+    fn test_argumentos_prioridad_sobre_entorno() {
+        let entorno = |nombre: &str| match nombre {
+            HOST_ENV => Some("0.0.0.0".to_string()),
+            PORT_ENV => Some("8000".to_string()),
+            BIND_ADDRESS_ENV => Some("10.0.0.1:7000".to_string()),
+            _ => None,
+        };
+
+        // HOST y PORT tienen prioridad sobre BIND_ADDRESS
+        let config = parse_sin_entorno(&["test"]).load_con_entorno(entorno).unwrap();
+        assert_eq!(config.servidor.bind_address(), "0.0.0.0:8000");
+
+        // Un argumento tiene prioridad sobre cualquier variable de entorno
+        let config = parse_sin_entorno(&["test", "--bind-address", "127.0.0.1:9000"]).load_con_entorno(entorno).unwrap();
+        assert_eq!(config.servidor.bind_address(), "127.0.0.1:9000");
+        assert!(config.to_toml().unwrap().contains("port = 9000"));
+
+        let config = parse_sin_entorno(&["test", "--bind-address", "127.0.0.1:9000", "--port", "9100"])
+            .load_con_entorno(entorno)
+            .unwrap();
+        assert_eq!(config.servidor.bind_address(), "127.0.0.1:9100");
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rmcp::model::{JsonObject, Tool};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use zen_engine::model::DecisionContent;
+use zen_engine::{DecisionEngine, EvaluationError, EvaluationOptions};
+
+use super::modelo::{CompiledDecision, DecisionModel, ModeloError};
+
+/// Fichero de manifiesto con las decisiones GoRules que se sirven como herramientas
+pub const DECISIONS_MANIFEST_ENV: &str = "DECISIONS_MANIFEST";
+
+/// Longitud máxima del nombre de una herramienta MCP
+const MAX_NOMBRE_HERRAMIENTA: usize = 64;
+
+// =================== ERRORES ===================
+
+#[derive(Debug)]
+pub enum DecisionError {
+    Lectura { path: PathBuf, source: std::io::Error },
+    Formato { path: PathBuf, message: String },
+    Nombre { nombre: String, message: String },
+    Modelo(ModeloError),
+}
+
+impl fmt::Display for DecisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecisionError::Lectura { path, source } => {
+                write!(f, "No se puede leer el manifiesto de decisiones '{}': {}", path.display(), source)
+            },
+            DecisionError::Formato { path, message } => {
+                write!(f, "El manifiesto de decisiones '{}' no es válido: {}", path.display(), message)
+            },
+            DecisionError::Nombre { nombre, message } => {
+                write!(f, "Nombre de herramienta '{}' no válido: {}", nombre, message)
+            },
+            DecisionError::Modelo(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DecisionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecisionError::Lectura { source, .. } => Some(source),
+            DecisionError::Modelo(e) => Some(e),
+            DecisionError::Formato { .. } | DecisionError::Nombre { .. } => None,
+        }
+    }
+}
+
+impl From<ModeloError> for DecisionError {
+    fn from(error: ModeloError) -> Self {
+        DecisionError::Modelo(error)
+    }
+}
+
+// =================== MANIFIESTO ===================
+
+/// Decisión del manifiesto que se publica como herramienta MCP
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EntradaManifiesto {
+    /// Nombre de la herramienta
+    pub name: String,
+    pub description: String,
+    /// Fichero GoRules JSON; las rutas relativas parten del directorio del manifiesto
+    pub path: PathBuf,
+}
+
+/// Manifiesto `.toml` o `.yaml` con una lista `decision` de decisiones a registrar
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Manifiesto {
+    #[serde(default, rename = "decision")]
+    pub decisiones: Vec<EntradaManifiesto>,
+}
+
+impl Manifiesto {
+    pub fn from_file(path: &Path) -> Result<Self, DecisionError> {
+        let contenido = std::fs::read_to_string(path)
+            .map_err(|source| DecisionError::Lectura { path: path.to_path_buf(), source })?;
+        let formato = |message: String| DecisionError::Formato { path: path.to_path_buf(), message };
+        let mut manifiesto: Self = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_str(&contenido).map_err(|e| formato(e.to_string()))?,
+            Some("toml") => toml::from_str(&contenido).map_err(|e| formato(e.to_string()))?,
+            _ => return Err(formato("la extensión debe ser .toml, .yaml o .yml".to_string())),
+        };
+        let base = path.parent().unwrap_or(Path::new(""));
+        for entrada in &mut manifiesto.decisiones {
+            entrada.path = base.join(&entrada.path);
+        }
+        Ok(manifiesto)
+    }
+}
+
+// =================== HERRAMIENTAS DE DECISIÓN ===================
+
+/// Decisión GoRules compilada y publicada como herramienta MCP. Los schemas de entrada
+/// y salida de la herramienta son los del `inputNode` y el `outputNode` del grafo
+#[derive(Debug, Clone)]
+pub struct HerramientaDecision {
+    pub tool: Tool,
+    pub decision: Arc<CompiledDecision>,
+    pub input_validator: Option<Arc<jsonschema::Validator>>,
+    /// Huella SHA-256 del fichero, como en los modelos de ayuda por excedencia
+    pub version: String,
+    pub path: PathBuf,
+}
+
+impl HerramientaDecision {
+    pub fn from_entry(entrada: &EntradaManifiesto) -> Result<Self, DecisionError> {
+        let path = entrada.path.clone();
+        if !path.is_file() {
+            return Err(ModeloError::NoEncontrado(path).into());
+        }
+        let raw = std::fs::read_to_string(&path)
+            .map_err(|source| ModeloError::Lectura { path: path.clone(), source })?;
+        let content: DecisionContent = serde_json::from_str(&raw)
+            .map_err(|source| ModeloError::Formato { path: path.clone(), source })?;
+
+        let input_schema = DecisionModel::extract_input_schema(&content);
+        let input_validator = match &input_schema {
+            Some(schema) => Some(DecisionModel::compile_validator(schema, &path)?),
+            None => None,
+        };
+        let output_schema = DecisionModel::extract_output_schema(&content)
+            .map(|schema| schema_objeto(schema, &path, "salida"))
+            .transpose()?;
+        let input_schema = match input_schema {
+            Some(schema) => schema_objeto(schema, &path, "entrada")?,
+            None => serde_json::json!({ "type": "object" }).as_object().cloned().unwrap_or_default(),
+        };
+
+        let decision = DecisionEngine::default().create_decision(Arc::new(content));
+        decision.validate().map_err(|source| ModeloError::Grafo { path: path.clone(), source })?;
+
+        let mut tool = Tool::new(entrada.name.clone(), entrada.description.clone(), Arc::new(input_schema));
+        tool.output_schema = output_schema.map(Arc::new);
+
+        Ok(Self {
+            tool,
+            decision: Arc::new(decision),
+            input_validator,
+            version: format!("sha256:{:x}", Sha256::digest(raw.as_bytes())),
+            path,
+        })
+    }
+
+    /// Evalúa la decisión con los argumentos de la llamada como contexto
+    pub async fn evaluate(&self, arguments: Value) -> Result<Value, Box<EvaluationError>> {
+        let options = EvaluationOptions { trace: Some(false), max_depth: None };
+        let result = self.decision.evaluate_with_opts(arguments.into(), options).await?;
+        Ok(result.result.into())
+    }
+}
+
+/// MCP exige que los schemas de una herramienta describan un objeto
+fn schema_objeto(schema: Value, path: &Path, nodo: &str) -> Result<JsonObject, ModeloError> {
+    match schema {
+        Value::Object(schema) if schema.get("type").is_none_or(|tipo| tipo == "object") => Ok(schema),
+        _ => Err(ModeloError::Schema {
+            path: path.to_path_buf(),
+            message: format!("el schema de {} de una herramienta debe ser de tipo 'object'", nodo),
+        }),
+    }
+}
+
+/// Herramientas de decisión registradas a partir del manifiesto, por nombre
+#[derive(Debug, Clone, Default)]
+pub struct DecisionRegistry {
+    herramientas: Arc<BTreeMap<String, Arc<HerramientaDecision>>>,
+}
+
+impl DecisionRegistry {
+    /// Compila las decisiones del manifiesto. `reservados` son los nombres de las herramientas
+    /// propias del servidor, que una decisión no puede sustituir
+    pub fn load(manifest: &Path, reservados: &[String]) -> Result<Self, DecisionError> {
+        let manifiesto = Manifiesto::from_file(manifest)?;
+        let mut herramientas = BTreeMap::new();
+        for entrada in &manifiesto.decisiones {
+            validar_nombre(&entrada.name)?;
+            if reservados.contains(&entrada.name) || herramientas.contains_key(&entrada.name) {
+                return Err(DecisionError::Nombre { nombre: entrada.name.clone(), message: "ya hay otra herramienta con ese nombre".to_string() });
+            }
+            let herramienta = HerramientaDecision::from_entry(entrada)?;
+            tracing::info!("Decisión '{}' registrada desde {} ({})", entrada.name, herramienta.path.display(), herramienta.version);
+            herramientas.insert(entrada.name.clone(), Arc::new(herramienta));
+        }
+        Ok(Self { herramientas: Arc::new(herramientas) })
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<HerramientaDecision>> {
+        self.herramientas.get(name).cloned()
+    }
+
+    pub fn tools(&self) -> Vec<Tool> {
+        self.herramientas.values().map(|herramienta| herramienta.tool.clone()).collect()
+    }
+}
+
+fn validar_nombre(nombre: &str) -> Result<(), DecisionError> {
+    let valido = !nombre.is_empty()
+        && nombre.len() <= MAX_NOMBRE_HERRAMIENTA
+        && nombre.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valido {
+        Ok(())
+    } else {
+        Err(DecisionError::Nombre {
+            nombre: nombre.to_string(),
+            message: format!("solo admite letras ASCII, dígitos, '_' y '-', hasta {} caracteres", MAX_NOMBRE_HERRAMIENTA),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::modelo::MODELO_EMBEBIDO;
+
+    #[tokio::test]
+    async fn test_registra_decisiones_del_manifiesto() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ayuda.json"), MODELO_EMBEBIDO).unwrap();
+        let manifest = dir.path().join("decisiones.toml");
+        std::fs::write(&manifest, "[[decision]]\nname = \"ayuda_excedencia\"\ndescription = \"Ayuda por excedencia\"\npath = \"ayuda.json\"\n").unwrap();
+
+        let registry = DecisionRegistry::load(&manifest, &[]).unwrap();
+        let herramienta = registry.get("ayuda_excedencia").unwrap();
+        let tool = &herramienta.tool;
+        assert_eq!(tool.description.as_deref(), Some("Ayuda por excedencia"));
+        assert!(tool.input_schema.contains_key("properties"));
+        assert!(tool.output_schema.is_some());
+
+        let resultado = herramienta.evaluate(serde_json::json!({
+            "input": { "parentesco": "madre", "situacion": "parto", "familia_monoparental": true, "numero_hijos": 1 }
+        })).await.unwrap();
+        assert_eq!(resultado["output"]["supuesto"], "E");
+
+        // Los nombres de las herramientas propias no se pueden sustituir
+        let result = DecisionRegistry::load(&manifest, &["ayuda_excedencia".to_string()]);
+        assert!(matches!(result, Err(DecisionError::Nombre { .. })));
+
+        std::fs::write(&manifest, "[[decision]]\nname = \"ayuda excedencia\"\ndescription = \"\"\npath = \"ayuda.json\"\n").unwrap();
+        assert!(matches!(DecisionRegistry::load(&manifest, &[]), Err(DecisionError::Nombre { .. })));
+
+        std::fs::write(&manifest, "[[decision]]\nname = \"otra\"\ndescription = \"\"\npath = \"no-existe.json\"\n").unwrap();
+        assert!(matches!(DecisionRegistry::load(&manifest, &[]), Err(DecisionError::Modelo(ModeloError::NoEncontrado(_)))));
+    }
+}
@@ -0,0 +1,103 @@
+use rmcp::model::JsonObject;
+use serde_json::Value;
+
+// =================== SCHEMA DE ENTRADA DEL MODELO ===================
+
+/// Propiedades de `input` declaradas en el schema del `inputNode` del modelo
+pub fn model_input_properties(model_schema: &Value) -> Option<&JsonObject> {
+    model_schema
+        .pointer("/properties/input/properties")
+        .and_then(Value::as_object)
+}
+
+/// Propiedades de `input` que el schema del modelo declara obligatorias
+pub fn model_input_required(model_schema: &Value) -> Vec<&str> {
+    model_schema
+        .pointer("/properties/input/required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Valores del `enum` que declara el modelo para una propiedad de `input`
+pub fn model_enum(model_schema: &Value, campo: &str) -> Option<Vec<String>> {
+    let values = model_input_properties(model_schema)?.get(campo)?.get("enum")?.as_array()?;
+    Some(values.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+}
+
+/// Copia en el schema de una herramienta los `enum` que declara el modelo para
+/// las propiedades con el mismo nombre, a cualquier nivel (incluidas `$defs` e `items`).
+/// Si la propiedad ya tiene un `enum` propio se deja la intersección de ambos.
+/// Así los clientes MCP ven exactamente los valores que aceptará el motor.
+pub fn apply_model_enums(tool_schema: &JsonObject, model_schema: &Value) -> JsonObject {
+    let mut schema = Value::Object(tool_schema.clone());
+    if let Some(model_properties) = model_input_properties(model_schema) {
+        apply_enums(&mut schema, model_properties);
+    }
+    match schema {
+        Value::Object(schema) => schema,
+        _ => tool_schema.clone(),
+    }
+}
+
+fn apply_enums(schema: &mut Value, model_properties: &JsonObject) {
+    match schema {
+        Value::Object(object) => {
+            if let Some(Value::Object(properties)) = object.get_mut("properties") {
+                for (name, property) in properties.iter_mut() {
+                    let model_enum = model_properties.get(name).and_then(|p| p.get("enum"));
+                    if let (Some(Value::Array(model_enum)), Value::Object(property)) = (model_enum, &mut *property) {
+                        let values = match property.get("enum") {
+                            Some(Value::Array(tool_enum)) => tool_enum.iter().filter(|v| model_enum.contains(v)).cloned().collect(),
+                            _ => model_enum.clone(),
+                        };
+                        property.insert("enum".to_string(), Value::Array(values));
+                    }
+                }
+            }
+            for value in object.values_mut() {
+                apply_enums(value, model_properties);
+            }
+        },
+        Value::Array(values) => {
+            for value in values {
+                apply_enums(value, model_properties);
+            }
+        },
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_model_enums() {
+        let model_schema = json!({
+            "properties": {"input": {"properties": {
+                "parentesco": {"type": "string", "enum": ["padre", "madre"]},
+                "situacion": {"type": "string", "enum": ["parto", "enfermedad", "accidente"]},
+                "familia_monoparental": {"type": "boolean"}
+            }}}
+        });
+        let tool_schema = json!({
+            "type": "object",
+            "properties": {
+                "parentesco": {"type": "string", "description": "Parentesco"},
+                "situacion": {"type": "string", "enum": ["parto", "nacimiento", "enfermedad"]},
+                "familia_monoparental": {"type": "boolean"}
+            },
+            "$defs": {"Caso": {"properties": {"parentesco": {"type": "string"}}}}
+        });
+
+        let merged = apply_model_enums(tool_schema.as_object().unwrap(), &model_schema);
+        let merged = Value::Object(merged);
+        assert_eq!(merged.pointer("/properties/parentesco/enum"), Some(&json!(["padre", "madre"])));
+        assert_eq!(merged.pointer("/properties/parentesco/description"), Some(&json!("Parentesco")));
+        assert_eq!(merged.pointer("/properties/situacion/enum"), Some(&json!(["parto", "enfermedad"])));
+        assert_eq!(merged.pointer("/properties/familia_monoparental/enum"), None);
+        assert_eq!(merged.pointer("/$defs/Caso/properties/parentesco/enum"), Some(&json!(["padre", "madre"])));
+    }
+}
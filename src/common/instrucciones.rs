@@ -0,0 +1,313 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use super::esquema::{model_enum, model_input_properties, model_input_required};
+use super::modelo::DecisionModel;
+use super::prompts::PROMPT_ENTREVISTA;
+use super::recursos::{CatalogoSupuestos, URI_SUPUESTOS};
+use super::valores::{Parentesco, Situacion};
+
+/// Campos con sus propios puntos en las instrucciones, fuera de la lista de campos del modelo
+const CAMPOS_CON_VALORES: &[&str] = &["parentesco", "situacion"];
+
+// =================== TEXTOS GENERADOS DEL MODELO ===================
+
+/// Valores que admiten a la vez el enum de Rust y el schema de entrada del modelo, en el orden
+/// del modelo. Son los mismos que anuncia el schema de las herramientas tras `apply_model_enums`
+pub fn valores_admitidos(model: Option<&DecisionModel>, campo: &str, valores: &[&str]) -> Vec<String> {
+    match model.and_then(|model| model_enum(model.input_schema.as_ref()?, campo)) {
+        Some(model_values) => model_values.into_iter().filter(|v| valores.contains(&v.as_str())).collect(),
+        None => valores.iter().map(|v| v.to_string()).collect(),
+    }
+}
+
+/// Supuesto del catálogo resumido: descripciones de las reglas con derecho e importe máximo
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumenSupuesto {
+    pub supuesto: String,
+    pub descripciones: Vec<String>,
+    pub importe_mensual: Option<i32>,
+}
+
+/// Un resumen por letra, en orden alfabético
+pub fn resumen_supuestos(catalogo: &CatalogoSupuestos) -> Vec<ResumenSupuesto> {
+    let mut por_letra: BTreeMap<&str, ResumenSupuesto> = BTreeMap::new();
+    for regla in &catalogo.supuestos {
+        let resumen = por_letra.entry(&regla.supuesto).or_insert_with(|| ResumenSupuesto {
+            supuesto: regla.supuesto.clone(),
+            descripciones: Vec::new(),
+            importe_mensual: None,
+        });
+        // Las reglas de denegación no describen el supuesto sino el motivo por el que no se cumple
+        if regla.tiene_derecho_potencial != Some(true) {
+            continue;
+        }
+        if !resumen.descripciones.contains(&regla.descripcion) {
+            resumen.descripciones.push(regla.descripcion.clone());
+        }
+        resumen.importe_mensual = resumen.importe_mensual.max(regla.importe_mensual);
+    }
+    por_letra.into_values().collect()
+}
+
+/// Campo de `input` del schema del modelo: nombre, tipo legible y descripción
+#[derive(Debug, Clone, PartialEq)]
+pub struct CampoEntrada {
+    pub nombre: String,
+    pub tipo: String,
+    pub obligatorio: bool,
+    pub descripcion: Option<String>,
+}
+
+impl CampoEntrada {
+    fn texto(&self) -> String {
+        let mut texto = format!("{} ({}", self.nombre, self.tipo);
+        if self.obligatorio {
+            texto.push_str(", obligatorio");
+        }
+        texto.push(')');
+        if let Some(descripcion) = &self.descripcion {
+            let _ = write!(texto, ": {}", descripcion);
+        }
+        texto
+    }
+}
+
+/// Campos de `input` del schema del modelo salvo parentesco y situación: primero los obligatorios
+/// y después el resto, cada grupo por orden alfabético
+pub fn campos_entrada(model: &DecisionModel) -> Vec<CampoEntrada> {
+    let Some(schema) = model.input_schema.as_ref() else {
+        return Vec::new();
+    };
+    let obligatorios = model_input_required(schema);
+    let mut campos: Vec<CampoEntrada> = model_input_properties(schema)
+        .map(|properties| {
+            properties
+                .iter()
+                .filter(|(nombre, _)| !CAMPOS_CON_VALORES.contains(&nombre.as_str()))
+                .map(|(nombre, property)| CampoEntrada {
+                    nombre: nombre.clone(),
+                    tipo: tipo_legible(property),
+                    obligatorio: obligatorios.contains(&nombre.as_str()),
+                    descripcion: property.get("description").and_then(Value::as_str).map(str::to_string),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    campos.sort_by_key(|campo| !campo.obligatorio);
+    campos
+}
+
+/// Tipo de una propiedad del schema tal y como se explica a quien llama a la herramienta
+fn tipo_legible(property: &Value) -> String {
+    if let Some(valores) = property.get("enum").and_then(Value::as_array) {
+        let valores: Vec<String> = valores.iter().map(|v| v.to_string()).collect();
+        return valores.join("/");
+    }
+    match property.get("type").and_then(Value::as_str) {
+        Some("boolean") => "true/false".to_string(),
+        Some("number" | "integer") => match (property.get("minimum"), property.get("maximum")) {
+            (Some(minimo), Some(maximo)) => format!("número de {} a {}", minimo, maximo),
+            (Some(minimo), None) => format!("número desde {}", minimo),
+            _ => "número".to_string(),
+        },
+        Some("string") if property.get("format").and_then(Value::as_str) == Some("date") => "fecha AAAA-MM-DD".to_string(),
+        Some("array") => match property.pointer("/items/properties").and_then(Value::as_object) {
+            Some(items) => format!("lista de objetos con {}", items.keys().cloned().collect::<Vec<_>>().join(", ")),
+            None => "lista".to_string(),
+        },
+        Some(tipo) => tipo.to_string(),
+        None => "valor".to_string(),
+    }
+}
+
+/// Estados de resolución que puede devolver la tabla de supuestos, en el orden de las reglas
+fn estados(catalogo: &CatalogoSupuestos) -> Vec<&str> {
+    let mut estados: Vec<&str> = Vec::new();
+    for estado in catalogo.supuestos.iter().filter_map(|regla| regla.estado.as_deref()) {
+        if !estados.contains(&estado) {
+            estados.push(estado);
+        }
+    }
+    estados
+}
+
+/// Supuestos con alguna regla en un estado, en orden alfabético
+fn supuestos_en_estado<'a>(catalogo: &'a CatalogoSupuestos, estado: &str) -> Vec<&'a str> {
+    let letras: BTreeSet<&str> = catalogo
+        .supuestos
+        .iter()
+        .filter(|regla| regla.estado.as_deref() == Some(estado))
+        .map(|regla| regla.supuesto.as_str())
+        .collect();
+    letras.into_iter().collect()
+}
+
+/// `instructions` del servidor: valores admitidos y campos del schema de entrada, y supuestos de la tabla de decisión
+pub fn instrucciones(model: Option<&DecisionModel>) -> String {
+    let catalogo = model.and_then(|model| CatalogoSupuestos::from_model(model).ok());
+
+    let mut texto = format!(
+        "Calculadora de ayudas para excedencia según la normativa de Navarra{}. \
+         \n\n** INSTRUCCIONES IMPORTANTES PARA USO DE HERRAMIENTAS **\
+         \n\n1. Use los valores especificados para cada parámetro; se admiten mayúsculas, tildes y sinónimos habituales ('Cónyuge', 'Adopción', 'nacimiento') y la respuesta indica el valor normalizado evaluado\
+         \n\n2. Para parentesco, use ÚNICAMENTE: {}\
+         \n\n3. Para situacion, use ÚNICAMENTE: {}",
+        model.map(|model| format!(" {}", model.ejercicio)).unwrap_or_default(),
+        citar(&valores_admitidos(model, "parentesco", Parentesco::VALORES), ", "),
+        citar(&valores_admitidos(model, "situacion", Situacion::VALORES), ", "),
+    );
+
+    let campos = model.map(campos_entrada).unwrap_or_default();
+    if campos.is_empty() {
+        texto.push_str("\n\n4. Para el resto de parámetros, consulte el schema de entrada de la herramienta");
+    } else {
+        texto.push_str("\n\n4. Resto de parámetros del schema de entrada del modelo:");
+        for campo in &campos {
+            let _ = write!(texto, "\n• {}", campo.texto());
+        }
+    }
+
+    if let Some(catalogo) = &catalogo {
+        let pendientes = supuestos_en_estado(catalogo, "pendiente_documentacion");
+        if !pendientes.is_empty() {
+            let _ = write!(
+                texto,
+                "\n\n5. Si faltan los datos que exige {} {}, el resultado queda en estado 'pendiente_documentacion' y errores o advertencias indican qué falta por acreditar",
+                if pendientes.len() == 1 { "el supuesto" } else { "los supuestos" },
+                enumerar(&pendientes)
+            );
+        }
+
+        texto.push_str("\n\nSUPUESTOS EVALUADOS:");
+        for resumen in resumen_supuestos(catalogo) {
+            let _ = write!(texto, "\n{}) {}", resumen.supuesto, resumen.descripciones.join(" / "));
+            if let Some(importe) = resumen.importe_mensual {
+                let _ = write!(texto, " ({}€/mes)", importe);
+            }
+        }
+        let _ = write!(texto, "\n\nEl catálogo completo de supuestos, con sus requisitos, está en el recurso {}", URI_SUPUESTOS);
+    }
+    let _ = write!(texto, "\n\nEl prompt {} guía la recogida de los datos pregunta a pregunta", PROMPT_ENTREVISTA);
+    texto
+}
+
+/// Descripción de `evaluar_supuesto_excedencia` con los supuestos, importes, valores y campos del modelo
+pub fn descripcion_evaluar(model: Option<&DecisionModel>) -> String {
+    let catalogo = model.and_then(|model| CatalogoSupuestos::from_model(model).ok());
+
+    let mut texto = format!(
+        "Evalúa el derecho a ayuda para excedencia según la normativa de Navarra{}. ",
+        model.map(|model| format!(" {}", model.ejercicio)).unwrap_or_default(),
+    );
+    match &catalogo {
+        Some(catalogo) => {
+            let resumenes = resumen_supuestos(catalogo);
+            let letras: Vec<&str> = resumenes.iter().map(|resumen| resumen.supuesto.as_str()).collect();
+            let importes: BTreeSet<i32> = catalogo.supuestos.iter().filter_map(|regla| regla.importe_mensual).collect();
+            let importes: Vec<String> = importes.iter().map(|importe| format!("{}€", importe)).collect();
+            let _ = write!(texto, "Determina supuesto ({}) e importe ({}). SUPUESTOS: ", letras.join("/"), importes.join("/"));
+            let supuestos: Vec<String> = resumenes
+                .iter()
+                .map(|resumen| match resumen.importe_mensual {
+                    Some(importe) => format!("{}={} ({}€)", resumen.supuesto, resumen.descripciones.join(" / "), importe),
+                    None => format!("{}={}", resumen.supuesto, resumen.descripciones.join(" / ")),
+                })
+                .collect();
+            let _ = write!(texto, "{}. ", supuestos.join(", "));
+        },
+        None => texto.push_str("Determina el supuesto aplicable y su importe mensual. "),
+    }
+    let _ = write!(
+        texto,
+        "USE VALORES EXACTOS: parentesco ({}), situacion ({}).",
+        citar(&valores_admitidos(model, "parentesco", Parentesco::VALORES), "/"),
+        citar(&valores_admitidos(model, "situacion", Situacion::VALORES), "/"),
+    );
+
+    let campos = model.map(campos_entrada).unwrap_or_default();
+    if !campos.is_empty() {
+        let campos: Vec<String> = campos.iter().map(CampoEntrada::texto).collect();
+        let _ = write!(texto, " OTROS CAMPOS: {}.", campos.join("; "));
+    }
+    if let Some(estados) = catalogo.as_ref().map(estados).filter(|estados| !estados.is_empty()) {
+        let _ = write!(
+            texto,
+            " El campo estado del resultado indica si el supuesto queda {}; errores lista los requisitos no cumplidos o por acreditar.",
+            enumerar(&estados)
+        );
+    }
+    texto
+}
+
+/// Descripción de `evaluar_supuestos_aplicables` con los supuestos de la tabla de decisión del modelo
+pub fn descripcion_supuestos_aplicables(model: Option<&DecisionModel>) -> String {
+    let letras: Vec<String> = model
+        .and_then(|model| CatalogoSupuestos::from_model(model).ok())
+        .map(|catalogo| resumen_supuestos(&catalogo).into_iter().map(|resumen| resumen.supuesto).collect())
+        .unwrap_or_default();
+    let supuestos = if letras.is_empty() { String::new() } else { format!(" ({})", letras.join("/")) };
+
+    format!(
+        "Evalúa TODOS los supuestos{} cuyas condiciones se cumplen, no solo el primero. \
+         evaluar_supuesto_excedencia aplica la prelación normativa y devuelve un único supuesto aunque se cumplan varios. \
+         Esta herramienta devuelve cada supuesto aplicable con su importe y requisitos, el primer supuesto aplicable en orden \
+         de prelación normativa (supuesto_prelacion) y el de mayor importe (supuesto_mas_favorable), para que la familia pueda \
+         elegir la opción más favorable. Mismos parámetros que evaluar_supuesto_excedencia.",
+        supuestos
+    )
+}
+
+/// Une los elementos como en una enumeración: `a, b o c`
+fn enumerar(elementos: &[&str]) -> String {
+    match elementos {
+        [] => String::new(),
+        [unico] => unico.to_string(),
+        [resto @ .., ultimo] => format!("{} o {}", resto.join(", "), ultimo),
+    }
+}
+
+fn citar(valores: &[String], separador: &str) -> String {
+    valores.iter().map(|v| format!("'{}'", v)).collect::<Vec<_>>().join(separador)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_textos_generados_del_modelo() {
+        let model = DecisionModel::embedded();
+
+        let texto = instrucciones(Some(&model));
+        assert!(texto.contains("normativa de Navarra 2025"));
+        assert!(texto.contains("'conyuge', 'pareja', 'esposo'"));
+        assert!(texto.contains("\nA) Cuidado familiar primer grado enfermo o que ha sufrido un accidente (725€/mes)"));
+        assert!(texto.contains("\nC) Adopción / Acogimiento (500€/mes)"));
+
+        // Los campos y sus explicaciones salen de las descripciones del schema de entrada
+        assert!(texto.contains("\n• familia_monoparental (true/false, obligatorio): ¿Es una familia monoparental"));
+        assert!(texto.contains("\n• hijos (lista de objetos con fecha_nacimiento, grado_discapacidad): Hijos, incluido el recién nacido"));
+        assert!(texto.contains("exige los supuestos A, B o C, el resultado queda en estado 'pendiente_documentacion'"));
+
+        let descripcion = descripcion_evaluar(Some(&model));
+        assert!(descripcion.contains("Determina supuesto (A/B/C/D/E) e importe (0€/500€/725€)"));
+        assert!(descripcion.contains("'conyuge'/'pareja'/'esposo'"));
+        assert!(descripcion.contains("fecha_hospitalizacion (fecha AAAA-MM-DD): Fecha de la hospitalización"));
+        assert!(descripcion.contains("duracion_acogimiento_meses (número desde 0)"));
+        assert!(descripcion.contains("OTROS CAMPOS: familia_monoparental (true/false, obligatorio)"));
+        assert!(descripcion.contains("queda reconocido, pendiente_documentacion o denegado"));
+
+        let descripcion = descripcion_supuestos_aplicables(Some(&model));
+        assert!(descripcion.starts_with("Evalúa TODOS los supuestos (A/B/C/D/E) cuyas condiciones se cumplen"));
+
+        // Sin modelo se anuncian los valores de Rust y no se inventan supuestos
+        assert!(descripcion_supuestos_aplicables(None).starts_with("Evalúa TODOS los supuestos cuyas condiciones"));
+        let descripcion = descripcion_evaluar(None);
+        assert!(descripcion.contains("'marido'"));
+        assert!(!descripcion.contains("SUPUESTOS"));
+        assert!(!instrucciones(None).contains("SUPUESTOS EVALUADOS"));
+    }
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+use super::calculadora::{ExcedenciaDirectParams, ExcedenciaResponse, ValidationError, deserialize_year_or_string};
+
+use rmcp::schemars;
+
+/// Número máximo de casos que se aceptan en una llamada a `evaluar_lote`
+pub const MAX_CASOS_LOTE: usize = 1000;
+
+// =================== ENTRADA DEL LOTE ===================
+
+/// Caso de un lote: los parámetros de `evaluar_supuesto_excedencia` más un identificador
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CasoLote {
+    #[schemars(description = "Identificador del caso asignado por quien llama (p. ej. número de empleado), texto o número. Se devuelve tal cual en el resultado")]
+    pub id: String,
+
+    #[serde(flatten)]
+    pub parametros: ExcedenciaDirectParams,
+}
+
+/// Caso del lote cuyos parámetros no se han podido interpretar
+#[derive(Debug, Clone, PartialEq)]
+pub struct CasoNoValido {
+    pub id: String,
+    pub error: String,
+    /// Valores de parentesco o situación que no se han podido interpretar
+    pub errores_validacion: Vec<ValidationError>,
+}
+
+/// Caso tal y como llega en el lote. Cada caso se interpreta por separado para que
+/// un caso con parámetros no válidos no haga fallar la llamada completa
+#[derive(Debug, PartialEq)]
+pub struct EntradaLote(pub Result<CasoLote, CasoNoValido>);
+
+impl<'de> Deserialize<'de> for EntradaLote {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut value = serde_json::Value::deserialize(deserializer)?;
+        // Se admiten ids numéricos (habituales en hojas de cálculo) y se tratan como texto
+        let id = match value.get("id") {
+            Some(serde_json::Value::String(id)) => id.clone(),
+            Some(id) => id.to_string(),
+            None => String::new(),
+        };
+        if let Some(id_value) = value.get_mut("id") {
+            *id_value = serde_json::Value::String(id.clone());
+        }
+        let errores_validacion = value.as_object().map(ValidationError::de_valores).unwrap_or_default();
+        Ok(EntradaLote(serde_json::from_value(value).map_err(|e| CasoNoValido {
+            id,
+            error: format!("Parámetros no válidos: {}", e),
+            errores_validacion,
+        })))
+    }
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct EvaluarLoteParams {
+    #[schemars(description = "Casos a evaluar, cada uno con su 'id' y los mismos parámetros que evaluar_supuesto_excedencia, descritos en el schema de cada caso. El número máximo de casos por llamada es 'maxItems'")]
+    #[schemars(with = "Vec<CasoLote>", length(max = MAX_CASOS_LOTE))]
+    pub casos: Vec<EntradaLote>,
+
+    #[schemars(description = "Ejercicio (año) de la normativa para los casos que no indiquen el suyo. Opcional: si se omite se aplica la normativa vigente")]
+    #[serde(default, deserialize_with = "deserialize_year_or_string")]
+    pub ejercicio: Option<i32>,
+}
+
+// =================== RESULTADO DEL LOTE ===================
+
+/// Resultado de un caso del lote: la evaluación o el error que la ha impedido
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ResultadoCaso {
+    #[schemars(description = "Identificador del caso indicado en la entrada")]
+    pub id: String,
+
+    #[schemars(description = "Resultado de la evaluación si el caso se ha podido evaluar")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resultado: Option<ExcedenciaResponse>,
+
+    #[schemars(description = "Motivo por el que el caso no se ha podido evaluar")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    #[schemars(description = "Errores de validación de los parámetros del caso, con el valor recibido y los permitidos")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errores_validacion: Vec<ValidationError>,
+}
+
+impl ResultadoCaso {
+    pub fn error(id: String, error: String) -> Self {
+        Self { id, resultado: None, error: Some(error), errores_validacion: Vec::new() }
+    }
+}
+
+/// Totales del lote
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ResumenLote {
+    #[schemars(description = "Número de casos recibidos")]
+    pub total: usize,
+
+    #[schemars(description = "Número de casos evaluados correctamente")]
+    pub evaluados: usize,
+
+    #[schemars(description = "Número de casos que no se han podido evaluar")]
+    pub con_error: usize,
+
+    #[schemars(description = "Número de casos evaluados por supuesto (A, B, C, D, E, NINGUNO)")]
+    pub por_supuesto: BTreeMap<String, usize>,
+
+    #[schemars(description = "Suma de los importes mensuales de todos los casos evaluados, en euros")]
+    pub importe_mensual_total: i64,
+}
+
+impl ResumenLote {
+    pub fn from_resultados(resultados: &[ResultadoCaso]) -> Self {
+        let mut resumen = ResumenLote { total: resultados.len(), ..Default::default() };
+        for resultado in resultados {
+            match &resultado.resultado {
+                Some(response) => {
+                    resumen.evaluados += 1;
+                    *resumen.por_supuesto.entry(response.output.supuesto.clone()).or_default() += 1;
+                    resumen.importe_mensual_total += i64::from(response.output.importe_mensual);
+                },
+                None => resumen.con_error += 1,
+            }
+        }
+        resumen
+    }
+}
+
+/// Resultado de `evaluar_lote`
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ResultadoLote {
+    #[schemars(description = "Totales del lote: casos evaluados y con error, casos por supuesto e importe mensual total")]
+    pub resumen: ResumenLote,
+
+    #[schemars(description = "Resultado de cada caso, en el mismo orden que la entrada")]
+    pub resultados: Vec<ResultadoCaso>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_caso_no_valido_no_invalida_el_lote() {
+        let params: EvaluarLoteParams = serde_json::from_value(json!({
+            "casos": [
+                {"id": "E-001", "parentesco": "Madre", "situacion": "parto", "familia_monoparental": false, "numero_hijos": 3},
+                {"id": 2, "parentesco": "hermano", "situacion": "parto", "familia_monoparental": false},
+                {"parentesco": "padre"}
+            ]
+        })).unwrap();
+
+        assert_eq!(params.casos.len(), 3);
+        let caso = params.casos[0].0.as_ref().unwrap();
+        assert_eq!(caso.id, "E-001");
+        assert_eq!(caso.parametros.numero_hijos, Some(3.0));
+
+        let error = params.casos[1].0.as_ref().unwrap_err();
+        assert_eq!(error.id, "2");
+        assert!(error.error.contains("hermano"));
+
+        let error = params.casos[2].0.as_ref().unwrap_err();
+        assert_eq!(error.id, "");
+        assert!(error.error.contains("id"));
+    }
+}
@@ -0,0 +1,201 @@
+use std::time::Duration;
+
+use prometheus::{Encoder, Histogram, HistogramOpts, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+
+pub use prometheus::TEXT_FORMAT;
+
+use super::calculadora::ValidationError;
+
+/// Prefijo de todas las métricas del servidor
+const PREFIJO_METRICAS: &str = "bon";
+
+/// Límites (segundos) del histograma de latencia de evaluación; una evaluación
+/// normal del grafo tarda del orden de cientos de microsegundos
+const BUCKETS_LATENCIA: &[f64] = &[0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0];
+
+/// Campos de `input` que se usan como etiqueta de los errores de validación. Cualquier otro
+/// se agrupa como [`CAMPO_OTRO`] para que una entrada arbitraria no multiplique las series
+const CAMPOS_INPUT: &[&str] = &[
+    "parentesco",
+    "situacion",
+    "familia_monoparental",
+    "numero_hijos",
+    "hijos",
+    "hospitalizacion",
+    "fecha_hospitalizacion",
+    "cuidado_continuado",
+    "duracion_acogimiento_meses",
+];
+
+/// Etiqueta de los errores de validación sobre campos que no están en [`CAMPOS_INPUT`]
+const CAMPO_OTRO: &str = "otro";
+
+// =================== MÉTRICAS ===================
+
+/// Métricas de uso de la calculadora, compartidas por todas las sesiones del proceso.
+/// Los servidores HTTP las publican en `/metrics` y el servidor stdio las vuelca al terminar
+#[derive(Debug, Clone)]
+pub struct Metricas {
+    registry: Registry,
+    evaluaciones: IntCounterVec,
+    evaluaciones_decision: IntCounterVec,
+    errores_validacion: IntCounterVec,
+    duracion_evaluacion: Histogram,
+    sesiones_activas: IntGauge,
+    llamadas_herramienta: IntCounterVec,
+}
+
+impl Default for Metricas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metricas {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some(PREFIJO_METRICAS.to_string()), None)
+            .expect("el prefijo de las métricas es válido");
+
+        let evaluaciones = IntCounterVec::new(
+            Opts::new("evaluaciones_total", "Evaluaciones del modelo de decisión por supuesto y derecho potencial"),
+            &["supuesto", "tiene_derecho_potencial"],
+        ).expect("métrica válida");
+        let evaluaciones_decision = IntCounterVec::new(
+            Opts::new("evaluaciones_decision_total", "Evaluaciones de las decisiones del manifiesto por herramienta y resultado"),
+            &["herramienta", "resultado"],
+        ).expect("métrica válida");
+        let errores_validacion = IntCounterVec::new(
+            Opts::new("errores_validacion_total", "Errores de validación de la entrada por campo"),
+            &["campo"],
+        ).expect("métrica válida");
+        let duracion_evaluacion = Histogram::with_opts(
+            HistogramOpts::new("evaluacion_duracion_segundos", "Duración de la evaluación del modelo de decisión")
+                .buckets(BUCKETS_LATENCIA.to_vec()),
+        ).expect("métrica válida");
+        let sesiones_activas = IntGauge::new("sesiones_activas", "Sesiones MCP inicializadas y abiertas")
+            .expect("métrica válida");
+        let llamadas_herramienta = IntCounterVec::new(
+            Opts::new("llamadas_herramienta_total", "Llamadas a herramientas MCP por nombre"),
+            &["herramienta"],
+        ).expect("métrica válida");
+
+        for metrica in [
+            Box::new(evaluaciones.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(evaluaciones_decision.clone()),
+            Box::new(errores_validacion.clone()),
+            Box::new(duracion_evaluacion.clone()),
+            Box::new(sesiones_activas.clone()),
+            Box::new(llamadas_herramienta.clone()),
+        ] {
+            registry.register(metrica).expect("cada métrica se registra una sola vez");
+        }
+
+        Self { registry, evaluaciones, evaluaciones_decision, errores_validacion, duracion_evaluacion, sesiones_activas, llamadas_herramienta }
+    }
+
+    /// Registra una evaluación del modelo con su resultado y duración
+    pub fn evaluacion(&self, supuesto: &str, tiene_derecho_potencial: bool, duracion: Duration) {
+        self.evaluaciones
+            .with_label_values(&[supuesto, if tiene_derecho_potencial { "true" } else { "false" }])
+            .inc();
+        self.duracion_evaluacion.observe(duracion.as_secs_f64());
+    }
+
+    /// Registra una evaluación de una decisión del manifiesto, que no tiene supuesto, con su
+    /// resultado (`ok` o `error`) y duración
+    pub fn evaluacion_decision(&self, herramienta: &str, correcta: bool, duracion: Duration) {
+        self.evaluaciones_decision
+            .with_label_values(&[herramienta, if correcta { "ok" } else { "error" }])
+            .inc();
+        self.duracion_evaluacion.observe(duracion.as_secs_f64());
+    }
+
+    /// Registra los errores de validación, agrupados por el campo de `input` afectado
+    pub fn errores_validacion(&self, errores: &[ValidationError]) {
+        for error in errores {
+            self.errores_validacion.with_label_values(&[campo_validacion(&error.path)]).inc();
+        }
+    }
+
+    /// Registra una llamada a herramienta
+    pub fn llamada_herramienta(&self, herramienta: &str) {
+        self.llamadas_herramienta.with_label_values(&[herramienta]).inc();
+    }
+
+    /// Cuenta una sesión abierta hasta que se suelte la guarda devuelta
+    pub fn sesion_activa(&self) -> GuardaSesion {
+        self.sesiones_activas.inc();
+        GuardaSesion(self.sesiones_activas.clone())
+    }
+
+    /// Métricas en el formato de texto de Prometheus
+    pub fn texto(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("las métricas se codifican en memoria");
+        String::from_utf8(buffer).expect("el formato de texto de Prometheus es UTF-8")
+    }
+}
+
+/// Campo de `input` al que se refiere un puntero JSON: `/input/hijos/0/fecha_nacimiento` → `hijos`.
+/// Los errores sobre el propio objeto (p. ej. un campo obligatorio ausente) se agrupan como `input`
+/// y los de campos desconocidos como [`CAMPO_OTRO`]
+fn campo_validacion(path: &str) -> &'static str {
+    let path = path.strip_prefix("/input").unwrap_or(path);
+    match path.trim_start_matches('/').split('/').next().unwrap_or_default() {
+        "" => "input",
+        campo => CAMPOS_INPUT.iter().find(|conocido| **conocido == campo).copied().unwrap_or(CAMPO_OTRO),
+    }
+}
+
+/// Mantiene contada una sesión MCP mientras exista
+#[derive(Debug)]
+pub struct GuardaSesion(IntGauge);
+
+impl Drop for GuardaSesion {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metricas_en_formato_prometheus() {
+        let metricas = Metricas::new();
+        metricas.evaluacion("A", true, Duration::from_micros(300));
+        metricas.evaluacion_decision("ayuda_generica", false, Duration::from_micros(200));
+        metricas.llamada_herramienta("evaluar_supuesto_excedencia");
+        metricas.errores_validacion(&[ValidationError {
+            message: "valor no permitido".to_string(),
+            path: "/input/parentesco".to_string(),
+            value: None,
+            allowed_values: None,
+        }]);
+        let sesion = metricas.sesion_activa();
+
+        let texto = metricas.texto();
+        assert!(texto.contains(r#"bon_evaluaciones_total{supuesto="A",tiene_derecho_potencial="true"} 1"#));
+        assert!(texto.contains(r#"bon_errores_validacion_total{campo="parentesco"} 1"#));
+        assert!(texto.contains(r#"bon_llamadas_herramienta_total{herramienta="evaluar_supuesto_excedencia"} 1"#));
+        assert!(texto.contains(r#"bon_evaluaciones_decision_total{herramienta="ayuda_generica",resultado="error"} 1"#));
+        assert!(texto.contains("bon_evaluacion_duracion_segundos_count 2"));
+        assert!(texto.contains("bon_sesiones_activas 1"));
+
+        drop(sesion);
+        assert!(metricas.texto().contains("bon_sesiones_activas 0"));
+    }
+
+    #[test]
+    fn test_campo_validacion() {
+        assert_eq!(campo_validacion("/input/parentesco"), "parentesco");
+        assert_eq!(campo_validacion("/input/hijos/0/fecha_nacimiento"), "hijos");
+        assert_eq!(campo_validacion("/input"), "input");
+        assert_eq!(campo_validacion(""), "input");
+        assert_eq!(campo_validacion("/input/campo_inventado"), "otro");
+        assert_eq!(campo_validacion("/otro_objeto/parentesco"), "otro");
+    }
+}
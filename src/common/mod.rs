@@ -1 +1,17 @@
+pub mod autenticacion;
 pub mod calculadora;
+pub mod configuracion;
+pub mod decisiones;
+pub mod esquema;
+pub mod instrucciones;
+pub mod lote;
+pub mod metricas;
+pub mod modelo;
+pub mod periodo;
+pub mod prompts;
+pub mod recarga;
+pub mod recursos;
+pub mod salud;
+pub mod transporte;
+pub mod traza;
+pub mod valores;
@@ -0,0 +1,526 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use chrono::Datelike;
+use rmcp::{Peer, RoleServer};
+use sha2::{Digest, Sha256};
+use zen_engine::handler::custom_node_adapter::NoopCustomNode;
+use zen_engine::loader::NoopLoader;
+use zen_engine::model::{DecisionContent, DecisionNode, DecisionNodeKind, DecisionTableHitPolicy};
+use zen_engine::{Decision, DecisionEngine, DecisionGraphValidationError};
+
+use super::decisiones::{DECISIONS_MANIFEST_ENV, DecisionError, DecisionRegistry};
+use super::recarga::spawn_model_watcher;
+use super::recursos::CatalogoSupuestos;
+use super::traza::NODO_SUPUESTOS;
+
+// =================== CONSTANTES ===================
+
+/// Prefijo de los ficheros de modelo; el nombre completo es `ayuda-excedencia-<ejercicio>.json`
+pub const PREFIJO_MODELO: &str = "ayuda-excedencia-";
+
+/// Ejercicio del modelo embebido en el binario
+pub const EJERCICIO_EMBEBIDO: i32 = 2025;
+
+/// Variable de entorno con la ruta al modelo de decisión (fichero o directorio)
+pub const DECISION_MODEL_PATH_ENV: &str = "DECISION_MODEL_PATH";
+
+/// Variable de entorno con el ejercicio de un fichero de modelo cuyo nombre no lo indica
+pub const DECISION_MODEL_EJERCICIO_ENV: &str = "DECISION_MODEL_EJERCICIO";
+
+/// Variable de entorno con el intervalo de comprobación de cambios del modelo (segundos, 0 desactiva)
+pub const DECISION_MODEL_RELOAD_SECS_ENV: &str = "DECISION_MODEL_RELOAD_SECS";
+
+/// Intervalo por defecto de comprobación de cambios del modelo, en segundos
+pub const DECISION_MODEL_RELOAD_SECS_POR_DEFECTO: u64 = 10;
+
+/// Modelo embebido en el binario, usado cuando no se configura una ruta externa
+pub const MODELO_EMBEBIDO: &str = include_str!("ayuda-excedencia-2025.json");
+
+// =================== ERRORES ===================
+
+#[derive(Debug)]
+pub enum ModeloError {
+    NoEncontrado(PathBuf),
+    Lectura { path: PathBuf, source: std::io::Error },
+    Formato { path: PathBuf, source: serde_json::Error },
+    Grafo { path: PathBuf, source: DecisionGraphValidationError },
+    Schema { path: PathBuf, message: String },
+}
+
+impl fmt::Display for ModeloError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModeloError::NoEncontrado(path) => {
+                write!(f, "No se encuentra el modelo de decisión en '{}'", path.display())
+            },
+            ModeloError::Lectura { path, source } => {
+                write!(f, "No se puede leer el modelo de decisión '{}': {}", path.display(), source)
+            },
+            ModeloError::Formato { path, source } => {
+                write!(f, "El modelo de decisión '{}' no es un DecisionContent válido: {}", path.display(), source)
+            },
+            ModeloError::Grafo { path, source } => {
+                write!(f, "El grafo del modelo de decisión '{}' no es válido: {}", path.display(), source)
+            },
+            ModeloError::Schema { path, message } => {
+                write!(f, "El schema de entrada del modelo de decisión '{}' no es válido: {}", path.display(), message)
+            },
+        }
+    }
+}
+
+impl std::error::Error for ModeloError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ModeloError::NoEncontrado(_) | ModeloError::Schema { .. } => None,
+            ModeloError::Lectura { source, .. } => Some(source),
+            ModeloError::Formato { source, .. } => Some(source),
+            ModeloError::Grafo { source, .. } => Some(source),
+        }
+    }
+}
+
+// =================== MODELO DE DECISIÓN ===================
+
+/// Procedencia del modelo de decisión cargado
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrigenModelo {
+    Embebido,
+    Fichero(PathBuf),
+}
+
+impl fmt::Display for OrigenModelo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrigenModelo::Embebido => write!(f, "embebido:{}{}.json", PREFIJO_MODELO, EJERCICIO_EMBEBIDO),
+            OrigenModelo::Fichero(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Decisión compilada lista para evaluar, compartible entre hilos
+pub type CompiledDecision = Decision<NoopLoader, NoopCustomNode>;
+
+/// Modelo de decisión GoRules deserializado y compilado una única vez
+#[derive(Debug, Clone)]
+pub struct DecisionModel {
+    pub ejercicio: i32,
+    /// Huella SHA-256 del fichero, identifica exactamente las reglas aplicadas
+    pub version: String,
+    pub decision: Arc<CompiledDecision>,
+    /// Variante de la decisión que recoge todas las reglas de la tabla de supuestos que se
+    /// cumplen (`hitPolicy: collect`) en lugar de quedarse con la primera
+    pub decision_supuestos: Option<Arc<CompiledDecision>>,
+    pub input_schema: Option<serde_json::Value>,
+    /// Validador compilado del schema de entrada; a diferencia de zen-engine devuelve todos los errores
+    pub input_validator: Option<Arc<jsonschema::Validator>>,
+    pub origen: OrigenModelo,
+    /// Grafo JSON tal y como se ha cargado, publicado como recurso `bon://modelo/{ejercicio}`
+    pub contenido: Arc<str>,
+}
+
+impl DecisionModel {
+    /// Modelo embebido en tiempo de compilación
+    pub fn embedded() -> Self {
+        Self::compile(MODELO_EMBEBIDO, EJERCICIO_EMBEBIDO, OrigenModelo::Embebido)
+            .expect("el modelo embebido debe ser un DecisionContent válido")
+    }
+
+    /// Deserializa y compila el contenido, validando el grafo antes de aceptarlo
+    fn compile(raw: &str, ejercicio: i32, origen: OrigenModelo) -> Result<Self, ModeloError> {
+        let path = match &origen {
+            OrigenModelo::Embebido => PathBuf::from(format!("{}{}.json", PREFIJO_MODELO, ejercicio)),
+            OrigenModelo::Fichero(path) => path.clone(),
+        };
+        let content: DecisionContent = serde_json::from_str(raw)
+            .map_err(|source| ModeloError::Formato { path: path.clone(), source })?;
+
+        let input_schema = Self::extract_input_schema(&content);
+        let input_validator = match &input_schema {
+            Some(schema) => Some(Self::compile_validator(schema, &path)?),
+            None => None,
+        };
+        let decision_supuestos = Self::collect_supuestos(&content)
+            .map(|content| DecisionEngine::default().create_decision(Arc::new(content)));
+        let decision = DecisionEngine::default().create_decision(Arc::new(content));
+        decision.validate().map_err(|source| ModeloError::Grafo { path: path.clone(), source })?;
+        // La variante `collect` también se valida, para no sustituir el modelo vigente por uno
+        // cuya evaluación de todos los supuestos falle
+        if let Some(decision_supuestos) = &decision_supuestos {
+            decision_supuestos.validate().map_err(|source| ModeloError::Grafo { path, source })?;
+        }
+
+        Ok(Self {
+            ejercicio,
+            version: format!("sha256:{:x}", Sha256::digest(raw.as_bytes())),
+            decision: Arc::new(decision),
+            decision_supuestos: decision_supuestos.map(Arc::new),
+            input_schema,
+            input_validator,
+            origen,
+            contenido: Arc::from(raw),
+        })
+    }
+
+    /// Copia del grafo con la tabla de supuestos en modo `collect`. El schema del `outputNode`
+    /// se elimina porque la tabla pasa a devolver una lista de resultados
+    fn collect_supuestos(content: &DecisionContent) -> Option<DecisionContent> {
+        let mut content = content.clone();
+        let mut found = false;
+        for node in content.nodes.iter_mut() {
+            let mut kind = node.kind.clone();
+            match &mut kind {
+                DecisionNodeKind::DecisionTableNode { content } if node.id == NODO_SUPUESTOS => {
+                    content.hit_policy = DecisionTableHitPolicy::Collect;
+                    found = true;
+                },
+                DecisionNodeKind::OutputNode { content } => content.schema = None,
+                _ => continue,
+            }
+            *node = Arc::new(DecisionNode { id: node.id.clone(), name: node.name.clone(), kind });
+        }
+        found.then_some(content)
+    }
+
+    /// Schema JSON declarado en el `inputNode` del grafo, si lo hay
+    pub fn extract_input_schema(content: &DecisionContent) -> Option<serde_json::Value> {
+        content.nodes.iter().find_map(|node| match &node.kind {
+            DecisionNodeKind::InputNode { content } => content
+                .schema
+                .as_deref()
+                .and_then(|schema| serde_json::from_str(schema).ok()),
+            _ => None,
+        })
+    }
+
+    /// Schema JSON declarado en el `outputNode` del grafo, si lo hay
+    pub fn extract_output_schema(content: &DecisionContent) -> Option<serde_json::Value> {
+        content.nodes.iter().find_map(|node| match &node.kind {
+            DecisionNodeKind::OutputNode { content } => content
+                .schema
+                .as_deref()
+                .and_then(|schema| serde_json::from_str(schema).ok()),
+            _ => None,
+        })
+    }
+
+    /// Compila el validador del schema de entrada
+    pub fn compile_validator(schema: &serde_json::Value, path: &Path) -> Result<Arc<jsonschema::Validator>, ModeloError> {
+        jsonschema::draft7::new(schema)
+            .map(Arc::new)
+            .map_err(|e| ModeloError::Schema { path: path.to_path_buf(), message: e.to_string() })
+    }
+
+    /// Carga un fichero de modelo. El ejercicio se toma del nombre (`ayuda-excedencia-<ejercicio>.json`)
+    /// o, si el nombre no lo indica, de `ejercicio_por_defecto`
+    pub fn from_path(path: &Path, ejercicio_por_defecto: i32) -> Result<Self, ModeloError> {
+        if !path.is_file() {
+            return Err(ModeloError::NoEncontrado(path.to_path_buf()));
+        }
+        let ejercicio = ejercicio_from_path(path).unwrap_or(ejercicio_por_defecto);
+
+        let raw = std::fs::read_to_string(path)
+            .map_err(|source| ModeloError::Lectura { path: path.to_path_buf(), source })?;
+
+        Self::compile(&raw, ejercicio, OrigenModelo::Fichero(path.to_path_buf()))
+    }
+}
+
+/// Ejercicio indicado en el nombre del fichero (`...-2025.json`)
+pub fn ejercicio_from_path(path: &Path) -> Option<i32> {
+    if path.extension()? != "json" {
+        return None;
+    }
+    let (_, year) = path.file_stem()?.to_str()?.rsplit_once('-')?;
+    if year.len() != 4 {
+        return None;
+    }
+    year.parse().ok()
+}
+
+/// Ficheros de modelo a cargar: el propio fichero o los `ayuda-excedencia-<año>.json` de un directorio.
+/// En un directorio solo se cargan los ficheros cuyo nombre indica el ejercicio
+pub fn model_files(path: &Path) -> Result<Vec<PathBuf>, ModeloError> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let entries = std::fs::read_dir(path)
+        .map_err(|source| ModeloError::Lectura { path: path.to_path_buf(), source })?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| {
+            file.is_file()
+                && file.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with(PREFIJO_MODELO))
+                && ejercicio_from_path(file).is_some()
+        })
+        .collect();
+    files.sort();
+
+    if files.is_empty() {
+        return Err(ModeloError::NoEncontrado(path.join(format!("{}<año>.json", PREFIJO_MODELO))));
+    }
+    Ok(files)
+}
+
+// =================== REGISTRO DE MODELOS ===================
+
+/// Modelos vigentes por ejercicio, compartidos por todas las sesiones y sustituibles en caliente
+#[derive(Debug, Clone)]
+pub struct ModelRegistry {
+    models: Arc<RwLock<BTreeMap<i32, Arc<DecisionModel>>>>,
+    peers: Arc<Mutex<Vec<Peer<RoleServer>>>>,
+}
+
+impl ModelRegistry {
+    pub fn new(models: impl IntoIterator<Item = DecisionModel>) -> Self {
+        let models = models
+            .into_iter()
+            .map(|model| (model.ejercicio, Arc::new(model)))
+            .collect();
+        Self {
+            models: Arc::new(RwLock::new(models)),
+            peers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Registro con únicamente el modelo embebido
+    pub fn embedded() -> Self {
+        Self::new([DecisionModel::embedded()])
+    }
+
+    /// Carga el registro desde un fichero o directorio o, si no hay ruta, el modelo embebido.
+    /// `ejercicio_por_defecto` es el ejercicio de un fichero cuyo nombre no lo indica
+    pub fn load(path: Option<&Path>, ejercicio_por_defecto: i32) -> Result<Self, ModeloError> {
+        let Some(path) = path else {
+            return Ok(Self::embedded());
+        };
+        let models = model_files(path)?
+            .iter()
+            .map(|file| DecisionModel::from_path(file, ejercicio_por_defecto))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(models))
+    }
+
+    /// Ejercicios disponibles, en orden ascendente
+    pub fn ejercicios(&self) -> Vec<i32> {
+        self.models.read().unwrap().keys().copied().collect()
+    }
+
+    /// Modelo de un ejercicio concreto
+    pub fn get(&self, ejercicio: i32) -> Option<Arc<DecisionModel>> {
+        self.models.read().unwrap().get(&ejercicio).cloned()
+    }
+
+    /// Modelo aplicable en un ejercicio: el suyo o, si aún no existe, el último anterior
+    pub fn applicable(&self, ejercicio: i32) -> Option<Arc<DecisionModel>> {
+        self.models
+            .read()
+            .unwrap()
+            .range(..=ejercicio)
+            .next_back()
+            .map(|(_, model)| model.clone())
+    }
+
+    /// Modelo aplicable a fecha de hoy
+    pub fn current(&self) -> Option<Arc<DecisionModel>> {
+        self.applicable(chrono::Local::now().year())
+    }
+
+    /// Añade o sustituye el modelo de su ejercicio. Devuelve `true` si cambia el schema de entrada
+    /// o la tabla de supuestos, de los que se generan los schemas y descripciones de las herramientas
+    pub fn insert(&self, model: Arc<DecisionModel>) -> bool {
+        let previous = self.models.write().unwrap().insert(model.ejercicio, model.clone());
+        let supuestos = |model: &DecisionModel| CatalogoSupuestos::from_model(model).ok().map(|catalogo| catalogo.supuestos);
+        previous.is_none_or(|previous| {
+            previous.input_schema != model.input_schema || supuestos(&previous) != supuestos(&model)
+        })
+    }
+
+    /// Registra una sesión MCP para avisarla cuando cambie la lista de herramientas
+    pub fn register_peer(&self, peer: Peer<RoleServer>) {
+        let mut peers = self.peers.lock().unwrap();
+        peers.retain(|peer| !peer.is_transport_closed());
+        peers.push(peer);
+    }
+
+    /// Envía `notifications/tools/list_changed` a todas las sesiones abiertas
+    pub fn notify_tool_list_changed(&self) {
+        let mut peers = self.peers.lock().unwrap();
+        peers.retain(|peer| !peer.is_transport_closed());
+        for peer in peers.iter() {
+            let peer = peer.clone();
+            tokio::spawn(async move {
+                if let Err(e) = peer.notify_tool_list_changed().await {
+                    tracing::warn!("No se pudo notificar el cambio de herramientas: {}", e);
+                }
+            });
+        }
+    }
+}
+
+// =================== ARGUMENTOS CLI ===================
+
+/// Origen de los modelos de decisión. Es la sección `[modelo]` de la configuración
+#[derive(Debug, Clone, Default, PartialEq, clap::Args, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelArgs {
+    /// Ruta a un modelo `ayuda-excedencia-<año>.json` o a un directorio con un modelo por ejercicio. Si no se indica se usa el modelo embebido
+    #[arg(long = "decision-model-path", env = DECISION_MODEL_PATH_ENV, value_name = "PATH")]
+    #[serde(rename = "path", skip_serializing_if = "Option::is_none")]
+    pub decision_model_path: Option<PathBuf>,
+
+    /// Ejercicio del fichero de modelo cuando su nombre no lo indica, como `/app/modelo.json` [por defecto: el del modelo embebido]
+    #[arg(long = "decision-model-ejercicio", env = DECISION_MODEL_EJERCICIO_ENV, value_name = "AÑO")]
+    #[serde(rename = "ejercicio", skip_serializing_if = "Option::is_none")]
+    pub decision_model_ejercicio: Option<i32>,
+
+    /// Intervalo en segundos para comprobar cambios en el modelo y recargarlo en caliente (0 desactiva) [por defecto: 10]
+    #[arg(long = "decision-model-reload-secs", env = DECISION_MODEL_RELOAD_SECS_ENV, value_name = "SECS")]
+    #[serde(rename = "reload_secs", skip_serializing_if = "Option::is_none")]
+    pub decision_model_reload_secs: Option<u64>,
+
+    /// Manifiesto `.toml` o `.yaml` con otras decisiones GoRules que se sirven como herramientas MCP (nombre, descripción y ruta de cada una)
+    #[arg(long = "decisions-manifest", env = DECISIONS_MANIFEST_ENV, value_name = "PATH")]
+    #[serde(rename = "decisiones", skip_serializing_if = "Option::is_none")]
+    pub decisions_manifest: Option<PathBuf>,
+}
+
+impl ModelArgs {
+    /// Ejercicio de un fichero de modelo cuyo nombre no lo indica
+    pub fn ejercicio_por_defecto(&self) -> i32 {
+        self.decision_model_ejercicio.unwrap_or(EJERCICIO_EMBEBIDO)
+    }
+
+    /// Carga en el registro los modelos de decisión indicados por los argumentos y, si
+    /// proceden de ficheros, arranca la recarga en caliente. El registro puede estar ya
+    /// compartido, así los servidores HTTP atienden `/health` mientras se compilan los modelos
+    pub fn load_models(&self, registry: &ModelRegistry) -> Result<(), ModeloError> {
+        let loaded = ModelRegistry::load(self.decision_model_path.as_deref(), self.ejercicio_por_defecto())
+            .inspect_err(|e| tracing::error!("{}", e))?;
+        for ejercicio in loaded.ejercicios() {
+            if let Some(model) = loaded.get(ejercicio) {
+                tracing::info!("Modelo de decisión {} cargado desde {} ({})", ejercicio, model.origen, model.version);
+                registry.insert(model);
+            }
+        }
+
+        let reload_secs = self.decision_model_reload_secs.unwrap_or(DECISION_MODEL_RELOAD_SECS_POR_DEFECTO);
+        if let Some(path) = &self.decision_model_path
+            && reload_secs > 0
+        {
+            spawn_model_watcher(registry.clone(), path.clone(), self.ejercicio_por_defecto(), Duration::from_secs(reload_secs));
+        }
+        Ok(())
+    }
+
+    /// Compila las decisiones del manifiesto, si se ha indicado. `reservados` son los nombres
+    /// de las herramientas propias del servidor
+    pub fn load_decisiones(&self, reservados: &[String]) -> Result<DecisionRegistry, DecisionError> {
+        match &self.decisions_manifest {
+            Some(manifest) => DecisionRegistry::load(manifest, reservados).inspect_err(|e| tracing::error!("{}", e)),
+            None => Ok(DecisionRegistry::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_embedded_when_no_path() {
+        let registry = ModelRegistry::load(None, EJERCICIO_EMBEBIDO).unwrap();
+        assert_eq!(registry.ejercicios(), vec![EJERCICIO_EMBEBIDO]);
+        let model = registry.get(EJERCICIO_EMBEBIDO).unwrap();
+        assert_eq!(model.origen, OrigenModelo::Embebido);
+        assert!(model.version.starts_with("sha256:"));
+        assert!(model.decision.validate().is_ok());
+        assert!(model.decision_supuestos.as_ref().unwrap().validate().is_ok());
+    }
+
+    #[test]
+    fn test_load_from_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ayuda-excedencia-2024.json"), MODELO_EMBEBIDO).unwrap();
+        std::fs::write(dir.path().join("ayuda-excedencia-2025.json"), MODELO_EMBEBIDO).unwrap();
+        std::fs::write(dir.path().join("notas.json"), "{}").unwrap();
+
+        let registry = ModelRegistry::load(Some(dir.path()), EJERCICIO_EMBEBIDO).unwrap();
+
+        assert_eq!(registry.ejercicios(), vec![2024, 2025]);
+        let model = registry.get(2024).unwrap();
+        assert_eq!(model.origen, OrigenModelo::Fichero(dir.path().join("ayuda-excedencia-2024.json")));
+    }
+
+    #[test]
+    fn test_applicable_uses_latest_previous_year() {
+        let mut model_2024 = DecisionModel::embedded();
+        model_2024.ejercicio = 2024;
+        let registry = ModelRegistry::new([model_2024, DecisionModel::embedded()]);
+
+        assert_eq!(registry.applicable(2024).unwrap().ejercicio, 2024);
+        assert_eq!(registry.applicable(2027).unwrap().ejercicio, 2025);
+        assert!(registry.applicable(2023).is_none());
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let result = DecisionModel::from_path(Path::new("/no/existe/ayuda-excedencia-2025.json"), EJERCICIO_EMBEBIDO);
+        assert!(matches!(result, Err(ModeloError::NoEncontrado(_))));
+    }
+
+    #[test]
+    fn test_file_without_year() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("modelo.json");
+        std::fs::write(&path, MODELO_EMBEBIDO).unwrap();
+
+        // Sin ejercicio explícito se usa el del modelo embebido
+        let registry = ModelRegistry::load(Some(&path), ModelArgs::default().ejercicio_por_defecto()).unwrap();
+        assert_eq!(registry.ejercicios(), vec![EJERCICIO_EMBEBIDO]);
+        assert_eq!(registry.get(EJERCICIO_EMBEBIDO).unwrap().origen, OrigenModelo::Fichero(path.clone()));
+
+        let args = ModelArgs { decision_model_ejercicio: Some(2026), ..Default::default() };
+        let registry = ModelRegistry::load(Some(&path), args.ejercicio_por_defecto()).unwrap();
+        assert_eq!(registry.ejercicios(), vec![2026]);
+
+        // En un directorio solo se cargan los ficheros cuyo nombre indica el ejercicio
+        let result = ModelRegistry::load(Some(dir.path()), args.ejercicio_por_defecto());
+        assert!(matches!(result, Err(ModeloError::NoEncontrado(_))));
+    }
+
+    #[test]
+    fn test_invalid_graph() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ayuda-excedencia-2025.json");
+        std::fs::write(&path, r#"{"nodes": [], "edges": []}"#).unwrap();
+        let result = DecisionModel::from_path(&path, EJERCICIO_EMBEBIDO);
+        assert!(matches!(result, Err(ModeloError::Grafo { .. })));
+    }
+
+    #[test]
+    fn test_insert_detects_schema_change() {
+        let registry = ModelRegistry::embedded();
+        assert!(!registry.insert(Arc::new(DecisionModel::embedded())));
+
+        let mut changed = DecisionModel::embedded();
+        changed.input_schema = None;
+        changed.input_validator = None;
+        assert!(registry.insert(Arc::new(changed)));
+        assert!(registry.get(EJERCICIO_EMBEBIDO).unwrap().input_schema.is_none());
+    }
+
+    #[test]
+    fn test_invalid_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ayuda-excedencia-2025.json");
+        std::fs::write(&path, r#"{"nodes": "no es una lista"}"#).unwrap();
+        let result = DecisionModel::from_path(&path, EJERCICIO_EMBEBIDO);
+        assert!(matches!(result, Err(ModeloError::Formato { .. })));
+    }
+}
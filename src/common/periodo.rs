@@ -0,0 +1,205 @@
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::calculadora::{ExcedenciaDirectParams, ExcedenciaInput, ModeloAplicado};
+
+use rmcp::schemars;
+
+/// Número máximo de años naturales que puede abarcar un periodo en `calcular_importe_periodo`
+pub const MAX_ANOS_PERIODO: i32 = 10;
+
+// =================== PARÁMETROS ===================
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ImportePeriodoParams {
+    #[serde(flatten)]
+    pub parametros: ExcedenciaDirectParams,
+
+    #[schemars(description = "Fecha de inicio de la excedencia (AAAA-MM-DD), incluida. Ejemplo: '2025-03-15'")]
+    pub fecha_inicio: NaiveDate,
+
+    #[schemars(description = "Fecha de fin de la excedencia (AAAA-MM-DD), incluida. Ejemplo: '2026-01-31'")]
+    pub fecha_fin: NaiveDate,
+}
+
+// =================== CALENDARIO ===================
+
+/// Mes natural completo o parcial dentro del periodo, con sus días de inicio y fin incluidos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TramoMes {
+    pub desde: NaiveDate,
+    pub hasta: NaiveDate,
+}
+
+impl TramoMes {
+    pub fn dias(&self) -> u32 {
+        (self.hasta - self.desde).num_days() as u32 + 1
+    }
+
+    pub fn dias_mes(&self) -> u32 {
+        dias_mes(self.desde)
+    }
+}
+
+/// Número de días del mes natural de la fecha
+pub fn dias_mes(fecha: NaiveDate) -> u32 {
+    let primero = fecha.with_day(1).expect("el día 1 existe en todos los meses");
+    let siguiente = primero + Months::new(1);
+    (siguiente - primero).num_days() as u32
+}
+
+/// Divide el periodo `[desde, hasta]` en tramos por mes natural
+pub fn tramos_mensuales(desde: NaiveDate, hasta: NaiveDate) -> Vec<TramoMes> {
+    let mut tramos = Vec::new();
+    let mut inicio = desde;
+    while inicio <= hasta {
+        let ultimo_dia = inicio.with_day(dias_mes(inicio)).expect("el último día del mes existe");
+        let fin = ultimo_dia.min(hasta);
+        tramos.push(TramoMes { desde: inicio, hasta: fin });
+        inicio = fin + chrono::Days::new(1);
+    }
+    tramos
+}
+
+/// Último día bonificable si el supuesto tiene una duración máxima en meses desde el inicio
+pub fn fin_duracion_maxima(inicio: NaiveDate, meses: u32) -> Option<NaiveDate> {
+    inicio.checked_add_months(Months::new(meses))?.pred_opt()
+}
+
+/// Importe de un tramo prorrateado por días naturales, redondeado a céntimos
+pub fn prorratear(importe_mensual: i32, dias: u32, dias_mes: u32) -> f64 {
+    let importe = f64::from(importe_mensual) * f64::from(dias) / f64::from(dias_mes);
+    (importe * 100.0).round() / 100.0
+}
+
+// =================== RESULTADO ===================
+
+/// Importe de un mes natural del periodo. El mes en que termina la duración máxima del
+/// supuesto se divide en dos: los días bonificables y el resto, con importe 0
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ImporteMes {
+    #[schemars(description = "Mes natural (AAAA-MM)")]
+    pub mes: String,
+
+    #[schemars(description = "Ejercicio cuya normativa se aplica al mes")]
+    pub ejercicio: i32,
+
+    #[schemars(description = "Primer día del mes dentro del periodo")]
+    pub desde: NaiveDate,
+
+    #[schemars(description = "Último día del mes dentro del periodo")]
+    pub hasta: NaiveDate,
+
+    #[schemars(description = "Días del mes dentro del periodo")]
+    pub dias: u32,
+
+    #[schemars(description = "Días naturales del mes")]
+    pub dias_mes: u32,
+
+    #[schemars(description = "Supuesto aplicado en el mes")]
+    pub supuesto: String,
+
+    #[schemars(description = "Importe mensual del supuesto en euros")]
+    pub importe_mensual: i32,
+
+    #[schemars(description = "Importe del mes en euros, prorrateado por días naturales si el mes es parcial; 0 tras fecha_fin_bonificable o sin derecho")]
+    pub importe: f64,
+}
+
+/// Parte del periodo sujeta a la normativa de un ejercicio
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct TramoEjercicio {
+    #[schemars(description = "Año natural del tramo")]
+    pub ejercicio: i32,
+
+    #[schemars(description = "Modelo de decisión aplicado al tramo")]
+    pub modelo: ModeloAplicado,
+
+    #[schemars(description = "Supuesto resultante con la normativa del tramo")]
+    pub supuesto: String,
+
+    #[schemars(description = "¿Tiene derecho potencial a la bonificación con la normativa del tramo?")]
+    pub tiene_derecho_potencial: bool,
+
+    #[schemars(description = "Importe mensual del supuesto en euros")]
+    pub importe_mensual: i32,
+
+    #[schemars(description = "Primer día bonificable del tramo")]
+    pub desde: NaiveDate,
+
+    #[schemars(description = "Último día bonificable del tramo")]
+    pub hasta: NaiveDate,
+
+    #[schemars(description = "Importe total del tramo en euros")]
+    pub importe: f64,
+}
+
+/// Resultado de `calcular_importe_periodo`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ImportePeriodo {
+    #[schemars(description = "Datos evaluados, con parentesco y situación normalizados a su valor canónico")]
+    pub input: ExcedenciaInput,
+
+    #[schemars(description = "Fecha de inicio de la excedencia")]
+    pub fecha_inicio: NaiveDate,
+
+    #[schemars(description = "Fecha de fin de la excedencia solicitada")]
+    pub fecha_fin: NaiveDate,
+
+    #[schemars(description = "Último día bonificable: la fecha de fin o, si es anterior, el fin de la duración máxima del supuesto")]
+    pub fecha_fin_bonificable: NaiveDate,
+
+    #[schemars(description = "Duración máxima del supuesto en meses, si el modelo de decisión la define")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duracion_maxima_meses: Option<u32>,
+
+    #[schemars(description = "Tramos del periodo por ejercicio, cada uno con la normativa de su año")]
+    pub tramos: Vec<TramoEjercicio>,
+
+    #[schemars(description = "Calendario mes a mes del periodo solicitado; los días posteriores a fecha_fin_bonificable figuran con importe 0")]
+    pub meses: Vec<ImporteMes>,
+
+    #[schemars(description = "Importe total del periodo en euros")]
+    pub importe_total: f64,
+
+    #[schemars(description = "Advertencias sobre el cálculo (duración máxima aplicada, meses sin derecho, etc.)")]
+    #[serde(default)]
+    pub advertencias: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fecha(texto: &str) -> NaiveDate {
+        texto.parse().unwrap()
+    }
+
+    #[test]
+    fn test_tramos_mensuales() {
+        let tramos = tramos_mensuales(fecha("2025-03-15"), fecha("2026-01-31"));
+        assert_eq!(tramos.len(), 11);
+        assert_eq!(tramos[0], TramoMes { desde: fecha("2025-03-15"), hasta: fecha("2025-03-31") });
+        assert_eq!(tramos[0].dias(), 17);
+        assert_eq!(tramos[0].dias_mes(), 31);
+        assert_eq!(tramos[10], TramoMes { desde: fecha("2026-01-01"), hasta: fecha("2026-01-31") });
+
+        let tramos = tramos_mensuales(fecha("2024-02-10"), fecha("2024-02-20"));
+        assert_eq!(tramos.len(), 1);
+        assert_eq!(tramos[0].dias(), 11);
+        assert_eq!(tramos[0].dias_mes(), 29);
+    }
+
+    #[test]
+    fn test_fin_duracion_maxima() {
+        assert_eq!(fin_duracion_maxima(fecha("2025-03-15"), 12), Some(fecha("2026-03-14")));
+        assert_eq!(fin_duracion_maxima(fecha("2025-01-01"), 1), Some(fecha("2025-01-31")));
+    }
+
+    #[test]
+    fn test_prorratear() {
+        assert_eq!(prorratear(500, 31, 31), 500.0);
+        assert_eq!(prorratear(500, 17, 31), 274.19);
+        assert_eq!(prorratear(725, 14, 28), 362.5);
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use rmcp::ErrorData as McpError;
+use rmcp::model::{GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole};
+use serde_json::Value;
+
+use super::calculadora::{EstadoResolucion, ExcedenciaResponse};
+use super::esquema::model_input_properties;
+use super::instrucciones::valores_admitidos;
+use super::modelo::{DecisionModel, ModelRegistry};
+use super::recursos::{CatalogoSupuestos, SupuestoCatalogo, URI_SUPUESTOS};
+use super::valores::{Parentesco, Situacion};
+
+/// Entrevista guiada para reunir los datos de `evaluar_supuesto_excedencia`
+pub const PROMPT_ENTREVISTA: &str = "entrevista_excedencia";
+
+/// Explicación para la ciudadanía de un resultado de `evaluar_supuesto_excedencia`
+pub const PROMPT_EXPLICAR: &str = "explicar_resultado";
+
+/// Campos por los que empieza la entrevista, en este orden
+const PREGUNTAS_BASICAS: &[&str] = &["parentesco", "situacion", "familia_monoparental", "numero_hijos", "hijos"];
+
+// =================== PROMPTS MCP ===================
+
+pub fn list_prompts() -> Vec<Prompt> {
+    vec![
+        Prompt::new(
+            PROMPT_ENTREVISTA,
+            Some("Entrevista guiada que pregunta parentesco, situación, monoparentalidad e hijos antes de evaluar el supuesto"),
+            Some(vec![argumento("ejercicio", "Ejercicio (año) cuya normativa se aplica; por defecto el vigente", false)]),
+        ),
+        Prompt::new(
+            PROMPT_EXPLICAR,
+            Some("Explica en lenguaje claro un resultado de evaluar_supuesto_excedencia"),
+            Some(vec![argumento("resultado", "Respuesta JSON de evaluar_supuesto_excedencia (ExcedenciaResponse)", true)]),
+        ),
+    ]
+}
+
+pub fn get_prompt(models: &ModelRegistry, name: &str, arguments: Option<&JsonObject>) -> Result<GetPromptResult, McpError> {
+    let argumento = |nombre: &str| arguments.and_then(|arguments| arguments.get(nombre));
+    match name {
+        PROMPT_ENTREVISTA => {
+            let ejercicio = match argumento("ejercicio") {
+                Some(Value::String(ejercicio)) => Some(ejercicio.trim().parse::<i32>().map_err(|_| {
+                    McpError::invalid_params(format!("Ejercicio no válido: '{}'", ejercicio), None)
+                })?),
+                Some(Value::Number(ejercicio)) => ejercicio.as_i64().map(|ejercicio| ejercicio as i32),
+                _ => None,
+            };
+            let model = modelo(models, ejercicio)?;
+            Ok(GetPromptResult {
+                description: Some(format!("Entrevista para la ayuda por excedencia (ejercicio {})", model.ejercicio)),
+                messages: vec![PromptMessage::new_text(PromptMessageRole::User, entrevista(&model, &catalogo(&model)?))],
+            })
+        },
+        PROMPT_EXPLICAR => {
+            // Los argumentos de un prompt llegan como texto, pero se acepta también el objeto JSON
+            let resultado = match argumento("resultado") {
+                Some(Value::String(resultado)) => serde_json::from_str::<ExcedenciaResponse>(resultado),
+                Some(resultado) => serde_json::from_value::<ExcedenciaResponse>(resultado.clone()),
+                None => return Err(McpError::invalid_params("Falta el argumento 'resultado'", None)),
+            }
+            .map_err(|e| McpError::invalid_params(format!("'resultado' no es una respuesta de evaluar_supuesto_excedencia: {}", e), None))?;
+
+            let model = modelo(models, resultado.modelo.as_ref().map(|modelo| modelo.ejercicio))?;
+            Ok(GetPromptResult {
+                description: Some(format!("Explicación del supuesto '{}'", resultado.output.supuesto)),
+                messages: vec![PromptMessage::new_text(PromptMessageRole::User, explicacion(&resultado, &catalogo(&model)?))],
+            })
+        },
+        _ => Err(McpError::invalid_params(format!("Prompt desconocido: {}", name), Some(serde_json::json!({ "prompt": name })))),
+    }
+}
+
+fn argumento(name: &str, description: &str, required: bool) -> PromptArgument {
+    PromptArgument { name: name.to_string(), description: Some(description.to_string()), required: Some(required) }
+}
+
+/// Modelo del ejercicio indicado (o el último anterior) o, sin ejercicio, el vigente
+fn modelo(models: &ModelRegistry, ejercicio: Option<i32>) -> Result<Arc<DecisionModel>, McpError> {
+    match ejercicio {
+        Some(ejercicio) => models.applicable(ejercicio).ok_or_else(|| {
+            McpError::invalid_params(format!("No hay modelo de decisión para el ejercicio {} (disponibles: {:?})", ejercicio, models.ejercicios()), None)
+        }),
+        None => models
+            .current()
+            .ok_or_else(|| McpError::internal_error("No hay modelo de decisión aplicable al ejercicio actual", None)),
+    }
+}
+
+fn catalogo(model: &DecisionModel) -> Result<CatalogoSupuestos, McpError> {
+    CatalogoSupuestos::from_model(model).map_err(|e| McpError::internal_error(e.to_string(), None))
+}
+
+// =================== TEXTO DE LOS PROMPTS ===================
+
+fn entrevista(model: &DecisionModel, catalogo: &CatalogoSupuestos) -> String {
+    let properties = model.input_schema.as_ref().and_then(model_input_properties);
+    let descripcion = |campo: &str| {
+        properties
+            .and_then(|properties| properties.get(campo)?.get("description")?.as_str())
+            .unwrap_or(campo)
+            .to_string()
+    };
+    let valores = |campo: &str, valores: &[&str]| valores_admitidos(Some(model), campo, valores);
+
+    let mut texto = format!(
+        "Vas a ayudar a una persona a comprobar si puede recibir la ayuda por excedencia (normativa del ejercicio {}). \
+         Hazle las preguntas de una en una, con lenguaje sencillo, y no supongas ningún dato: si no sabe una respuesta, anótalo y sigue.\n\n\
+         PREGUNTAS\n",
+        model.ejercicio
+    );
+    let _ = writeln!(texto, "1. {}. Valores admitidos: {}", descripcion("parentesco"), valores("parentesco", Parentesco::VALORES).join(", "));
+    let _ = writeln!(texto, "2. {}. Valores admitidos: {}", descripcion("situacion"), valores("situacion", Situacion::VALORES).join(", "));
+    let _ = writeln!(texto, "3. {} (sí o no)", descripcion("familia_monoparental"));
+    let _ = writeln!(texto, "4. {}", descripcion("numero_hijos"));
+    let _ = writeln!(texto, "5. {}: fecha de nacimiento (AAAA-MM-DD) y grado de discapacidad de cada hijo, si lo tiene", descripcion("hijos"));
+
+    let adicionales: Vec<(&String, String)> = properties
+        .map(|properties| {
+            properties
+                .keys()
+                .filter(|campo| !PREGUNTAS_BASICAS.contains(&campo.as_str()))
+                .map(|campo| (campo, descripcion(campo)))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !adicionales.is_empty() {
+        texto.push_str("\nPREGUNTAS SEGÚN LA SITUACIÓN\nPregunta solo las que correspondan al supuesto que pueda aplicar:\n");
+        for (campo, descripcion) in adicionales {
+            let _ = writeln!(texto, "- {} ({})", descripcion, campo);
+        }
+    }
+
+    texto.push_str("\nSUPUESTOS DE LA NORMATIVA\nReglas de la tabla de decisión, agrupadas por supuesto:\n");
+    texto.push_str(&resumen_catalogo(&catalogo.supuestos));
+    let _ = write!(
+        texto,
+        "\nCuando tengas las respuestas, resúmelas, pide confirmación y llama a la herramienta evaluar_supuesto_excedencia con ellas. \
+         El catálogo completo está en el recurso {}.",
+        URI_SUPUESTOS
+    );
+    texto
+}
+
+fn explicacion(resultado: &ExcedenciaResponse, catalogo: &CatalogoSupuestos) -> String {
+    let output = &resultado.output;
+    let estado = match output.estado {
+        Some(EstadoResolucion::Reconocido) => "reconocido: se cumplen los requisitos que se pueden comprobar",
+        Some(EstadoResolucion::Denegado) => "denegado: no se cumple algún requisito",
+        Some(EstadoResolucion::PendienteDocumentacion) => "pendiente de documentación: faltan datos para acreditar algún requisito",
+        None if output.tiene_derecho_potencial => "con derecho potencial",
+        None => "sin derecho",
+    };
+
+    let mut texto = String::from(
+        "Explica a la persona solicitante, en lenguaje claro y sin tecnicismos, el resultado de su consulta sobre la ayuda por excedencia. \
+         Di qué supuesto aplica y por qué, el importe mensual, qué falta o qué no se cumple y los siguientes pasos. \
+         No añadas requisitos ni importes que no aparezcan abajo y recuerda que es una estimación, no una resolución oficial.\n\n\
+         RESULTADO\n",
+    );
+    let _ = writeln!(texto, "- Supuesto: {}", if output.supuesto.is_empty() { "ninguno" } else { &output.supuesto });
+    let _ = writeln!(texto, "- Descripción: {}", output.descripcion);
+    let _ = writeln!(texto, "- Importe mensual: {} €", output.importe_mensual);
+    let _ = writeln!(texto, "- Estado: {}", estado);
+    if let Some(meses) = output.duracion_maxima_meses {
+        let _ = writeln!(texto, "- Duración máxima: {} meses", meses);
+    }
+    if !output.requisitos_adicionales.is_empty() {
+        let _ = writeln!(texto, "- Requisitos adicionales: {}", output.requisitos_adicionales);
+    }
+    for error in &output.errores {
+        let _ = writeln!(texto, "- Requisito no cumplido o pendiente: {}", error);
+    }
+    for advertencia in &output.advertencias {
+        let _ = writeln!(texto, "- Advertencia: {}", advertencia);
+    }
+
+    let reglas: Vec<SupuestoCatalogo> = catalogo
+        .supuestos
+        .iter()
+        .filter(|supuesto| supuesto.supuesto == output.supuesto)
+        .cloned()
+        .collect();
+    if !reglas.is_empty() {
+        let _ = write!(texto, "\nREGLAS DEL SUPUESTO {} (ejercicio {})\n", output.supuesto, catalogo.ejercicio);
+        texto.push_str(&resumen_catalogo(&reglas));
+    }
+    texto
+}
+
+/// Reglas del catálogo agrupadas por supuesto, una línea por regla
+fn resumen_catalogo(supuestos: &[SupuestoCatalogo]) -> String {
+    let mut por_letra: BTreeMap<&str, Vec<&SupuestoCatalogo>> = BTreeMap::new();
+    for supuesto in supuestos {
+        por_letra.entry(&supuesto.supuesto).or_default().push(supuesto);
+    }
+
+    let mut texto = String::new();
+    for (letra, reglas) in por_letra {
+        let _ = writeln!(texto, "Supuesto {}:", letra);
+        for regla in reglas {
+            let _ = write!(texto, "- {}", regla.descripcion);
+            if let Some(importe) = regla.importe_mensual {
+                let _ = write!(texto, ", {} €/mes", importe);
+            }
+            if let Some(estado) = &regla.estado {
+                let _ = write!(texto, ", {}", estado);
+            }
+            if !regla.requisitos.is_empty() {
+                let condiciones: Vec<String> = regla.requisitos.iter().map(|(campo, condicion)| format!("{} {}", campo, condicion)).collect();
+                let _ = write!(texto, ". Condiciones: {}", condiciones.join("; "));
+            }
+            if let Some(requisitos) = &regla.requisitos_adicionales {
+                let _ = write!(texto, ". {}", requisitos);
+            }
+            texto.push('\n');
+        }
+    }
+    texto
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::PromptMessageContent;
+
+    fn texto(result: &GetPromptResult) -> &str {
+        match &result.messages[0].content {
+            PromptMessageContent::Text { text } => text,
+            _ => panic!("se esperaba un mensaje de texto"),
+        }
+    }
+
+    #[test]
+    fn test_entrevista_desde_el_modelo() {
+        let models = ModelRegistry::embedded();
+        let arguments = serde_json::json!({ "ejercicio": "2025" });
+        let result = get_prompt(&models, PROMPT_ENTREVISTA, arguments.as_object()).unwrap();
+        let texto = texto(&result);
+
+        assert!(texto.contains("padre, madre, hijo, hija, conyuge"));
+        assert!(texto.contains("parto_multiple"));
+        assert!(texto.contains("(cuidado_continuado)"));
+        for letra in ["A", "B", "C", "D", "E"] {
+            assert!(texto.contains(&format!("Supuesto {}:", letra)));
+        }
+        assert!(texto.contains("725 €/mes"));
+
+        let arguments = serde_json::json!({ "ejercicio": "dos mil" });
+        assert!(get_prompt(&models, PROMPT_ENTREVISTA, arguments.as_object()).is_err());
+    }
+
+    #[test]
+    fn test_explicar_resultado() {
+        let models = ModelRegistry::embedded();
+        let resultado = serde_json::json!({
+            "output": {
+                "descripcion": "Familia monoparental con recién nacido",
+                "importe_mensual": 500,
+                "supuesto": "E",
+                "tiene_derecho_potencial": true,
+                "estado": "reconocido"
+            }
+        });
+        let arguments = serde_json::json!({ "resultado": resultado.to_string() });
+        let result = get_prompt(&models, PROMPT_EXPLICAR, arguments.as_object()).unwrap();
+        let texto = texto(&result);
+
+        assert!(texto.contains("- Importe mensual: 500 €"));
+        assert!(texto.contains("REGLAS DEL SUPUESTO E"));
+        assert!(!texto.contains("Supuesto A:"));
+
+        let arguments = serde_json::json!({ "resultado": "{}" });
+        assert!(get_prompt(&models, PROMPT_EXPLICAR, arguments.as_object()).is_err());
+        assert!(get_prompt(&models, "otro", None).is_err());
+    }
+}
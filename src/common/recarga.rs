@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use super::modelo::{DecisionModel, ModelRegistry, ModeloError, model_files};
+
+// =================== RECARGA EN CALIENTE ===================
+
+/// Marca de un fichero usada para detectar cambios: fecha de modificación y tamaño
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Marcas actuales de los ficheros de modelo bajo `path` (fichero o directorio)
+fn model_stamps(path: &Path) -> HashMap<PathBuf, (SystemTime, u64)> {
+    model_files(path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|file| file_stamp(&file).map(|stamp| (file, stamp)))
+        .collect()
+}
+
+/// Comprueba periódicamente los ficheros de modelo y sustituye la decisión compilada
+/// del ejercicio que cambie. Los ficheros nuevos de un directorio se incorporan como
+/// nuevos ejercicios. Si un fichero no es válido se mantiene el modelo anterior.
+/// `ejercicio_por_defecto` es el ejercicio de un fichero cuyo nombre no lo indica.
+pub fn spawn_model_watcher(registry: ModelRegistry, path: PathBuf, ejercicio_por_defecto: i32, interval: Duration) -> JoinHandle<()> {
+    tracing::info!(
+        "Vigilando cambios en los modelos de decisión de {} cada {:?}",
+        path.display(),
+        interval
+    );
+
+    // Leer los ficheros y compilar el grafo bloquea: se hace en hilos blocking para no
+    // ocupar los workers del runtime en cada comprobación
+    tokio::spawn(async move {
+        let stamps_path = path.clone();
+        let mut last_stamps = tokio::task::spawn_blocking(move || model_stamps(&stamps_path)).await.unwrap_or_default();
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+
+            let (registry, path, previous) = (registry.clone(), path.clone(), std::mem::take(&mut last_stamps));
+            match tokio::task::spawn_blocking(move || reload_changed(&registry, &path, ejercicio_por_defecto, &previous)).await {
+                Ok(stamps) => last_stamps = stamps,
+                // Sin marcas previas, la siguiente comprobación vuelve a cargar todos los ficheros
+                Err(e) => tracing::error!("Error interno al comprobar los modelos de decisión: {}", e),
+            }
+        }
+    })
+}
+
+/// Recarga los ficheros de modelo cuya marca ha cambiado desde `previous` y avisa a las
+/// sesiones si cambian las herramientas. Devuelve las marcas actuales
+fn reload_changed(
+    registry: &ModelRegistry,
+    path: &Path,
+    ejercicio_por_defecto: i32,
+    previous: &HashMap<PathBuf, (SystemTime, u64)>,
+) -> HashMap<PathBuf, (SystemTime, u64)> {
+    let stamps = model_stamps(path);
+    let mut tools_changed = false;
+    for (file, stamp) in &stamps {
+        if previous.get(file) != Some(stamp) {
+            match reload_model(registry, file, ejercicio_por_defecto) {
+                Ok(changed) => tools_changed |= changed,
+                Err(e) => tracing::error!("{}. Se mantiene el modelo de decisión anterior", e),
+            }
+        }
+    }
+
+    if tools_changed {
+        registry.notify_tool_list_changed();
+    }
+    stamps
+}
+
+/// Recarga el modelo de `path`, validándolo antes de sustituir el vigente.
+/// Devuelve `true` si se ha sustituido y han cambiado el schema de entrada o la tabla de supuestos;
+/// si el fichero no es válido el registro no se modifica.
+pub fn reload_model(registry: &ModelRegistry, path: &Path, ejercicio_por_defecto: i32) -> Result<bool, ModeloError> {
+    let new_model = DecisionModel::from_path(path, ejercicio_por_defecto)?;
+    let ejercicio = new_model.ejercicio;
+    let version = new_model.version.clone();
+    let tools_changed = registry.insert(Arc::new(new_model));
+    tracing::info!(
+        "Modelo de decisión {} recargado desde {} ({}, herramientas modificadas: {})",
+        ejercicio,
+        path.display(),
+        version,
+        tools_changed
+    );
+    Ok(tools_changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::modelo::{EJERCICIO_EMBEBIDO, MODELO_EMBEBIDO};
+
+    #[tokio::test]
+    async fn test_reload_keeps_previous_model_on_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ayuda-excedencia-2025.json");
+        std::fs::write(&path, MODELO_EMBEBIDO).unwrap();
+        let registry = ModelRegistry::load(Some(dir.path()), EJERCICIO_EMBEBIDO).unwrap();
+        let before = registry.get(2025).unwrap();
+
+        std::fs::write(&path, "{ roto").unwrap();
+        assert!(reload_model(&registry, &path, EJERCICIO_EMBEBIDO).is_err());
+        assert!(Arc::ptr_eq(&before, &registry.get(2025).unwrap()));
+
+        std::fs::write(&path, MODELO_EMBEBIDO).unwrap();
+        assert!(!reload_model(&registry, &path, EJERCICIO_EMBEBIDO).unwrap());
+        assert!(!Arc::ptr_eq(&before, &registry.get(2025).unwrap()));
+
+        // Un cambio en la tabla de supuestos cambia la descripción de las herramientas
+        let modificado = MODELO_EMBEBIDO.replace("\"output-importe-mensual\": \"725\"", "\"output-importe-mensual\": \"750\"");
+        std::fs::write(&path, modificado).unwrap();
+        assert!(reload_model(&registry, &path, EJERCICIO_EMBEBIDO).unwrap());
+
+        let new_year = dir.path().join("ayuda-excedencia-2026.json");
+        std::fs::write(&new_year, MODELO_EMBEBIDO).unwrap();
+        assert!(reload_model(&registry, &new_year, EJERCICIO_EMBEBIDO).unwrap());
+        assert_eq!(registry.ejercicios(), vec![2025, 2026]);
+
+        // La comprobación periódica solo recarga los ficheros cuya marca ha cambiado
+        let stamps = reload_changed(&registry, dir.path(), EJERCICIO_EMBEBIDO, &HashMap::new());
+        assert_eq!(stamps.len(), 2);
+        let vigente = registry.get(2026).unwrap();
+        assert_eq!(reload_changed(&registry, dir.path(), EJERCICIO_EMBEBIDO, &stamps), stamps);
+        assert!(Arc::ptr_eq(&vigente, &registry.get(2026).unwrap()));
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rmcp::ErrorData as McpError;
+use rmcp::model::{AnnotateAble, RawResource, RawResourceTemplate, Resource, ResourceContents, ResourceTemplate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zen_engine::model::{DecisionContent, DecisionNodeKind, DecisionTableContent};
+
+use super::modelo::{DecisionModel, ModelRegistry};
+use super::traza::NODO_SUPUESTOS;
+
+/// Catálogo de supuestos del modelo vigente
+pub const URI_SUPUESTOS: &str = "bon://supuestos";
+
+/// Prefijo de los recursos por ejercicio: `bon://modelo/{ejercicio}` y `bon://modelo/{ejercicio}/esquema`
+pub const PREFIJO_URI_MODELO: &str = "bon://modelo/";
+
+/// Valor de `output.supuesto` en las reglas que no reconocen ningún supuesto
+pub const SIN_SUPUESTO: &str = "NINGUNO";
+
+const MIME_JSON: &str = "application/json";
+
+// =================== CATÁLOGO DE SUPUESTOS ===================
+
+/// Fila de la tabla de supuestos del modelo de decisión
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SupuestoCatalogo {
+    /// Letra del supuesto (A-E)
+    pub supuesto: String,
+    /// Identificador de la regla (`_id`) en la tabla de decisión
+    pub regla: String,
+    pub descripcion: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub importe_mensual: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tiene_derecho_potencial: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estado: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duracion_maxima_meses: Option<u32>,
+    /// Condiciones de la regla por campo de entrada, tal y como están escritas en la tabla
+    pub requisitos: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requisitos_adicionales: Option<String>,
+}
+
+/// Contenido del recurso `bon://supuestos`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CatalogoSupuestos {
+    pub ejercicio: i32,
+    pub version: String,
+    /// Reglas en el orden de la tabla: se aplica la primera que coincide
+    pub supuestos: Vec<SupuestoCatalogo>,
+}
+
+impl CatalogoSupuestos {
+    /// Genera el catálogo a partir de las reglas de la tabla de supuestos del modelo
+    pub fn from_model(model: &DecisionModel) -> Result<Self, serde_json::Error> {
+        let content: DecisionContent = serde_json::from_str(&model.contenido)?;
+        let supuestos = content
+            .nodes
+            .iter()
+            .find_map(|node| match &node.kind {
+                DecisionNodeKind::DecisionTableNode { content } if node.id == NODO_SUPUESTOS => Some(supuestos(content)),
+                _ => None,
+            })
+            .unwrap_or_default();
+        Ok(Self { ejercicio: model.ejercicio, version: model.version.clone(), supuestos })
+    }
+}
+
+fn supuestos(table: &DecisionTableContent) -> Vec<SupuestoCatalogo> {
+    // Columnas de salida por campo (`output.supuesto` -> id de columna)
+    let outputs: HashMap<&str, &str> = table
+        .outputs
+        .iter()
+        .map(|output| (output.field.as_str(), output.id.as_str()))
+        .collect();
+
+    table
+        .rules
+        .iter()
+        .filter_map(|rule| {
+            // Las celdas de salida son expresiones; el catálogo solo recoge las que son literales
+            let salida = |campo: &str| -> Option<Value> {
+                let celda = rule.get(*outputs.get(campo)?)?;
+                serde_json::from_str(celda).ok()
+            };
+            let texto = |campo: &str| salida(campo).and_then(|v| v.as_str().map(str::to_string));
+
+            let supuesto = texto("output.supuesto").filter(|supuesto| supuesto != SIN_SUPUESTO)?;
+            let requisitos = table
+                .inputs
+                .iter()
+                .filter_map(|input| {
+                    let condicion = rule.get(&input.id).filter(|c| !c.is_empty())?;
+                    Some((input.field.clone()?, condicion.clone()))
+                })
+                .collect();
+
+            Some(SupuestoCatalogo {
+                supuesto,
+                regla: rule.get("_id").cloned().unwrap_or_default(),
+                descripcion: texto("output.descripcion")
+                    .or_else(|| rule.get("_description").cloned())
+                    .unwrap_or_default(),
+                importe_mensual: salida("output.importe_mensual").and_then(|v| v.as_i64()).map(|v| v as i32),
+                tiene_derecho_potencial: salida("output.tiene_derecho_potencial").and_then(|v| v.as_bool()),
+                estado: texto("output.estado"),
+                duracion_maxima_meses: salida("output.duracion_maxima_meses").and_then(|v| v.as_u64()).map(|v| v as u32),
+                requisitos,
+                requisitos_adicionales: texto("output.requisitos_adicionales"),
+            })
+        })
+        .collect()
+}
+
+// =================== RECURSOS MCP ===================
+
+/// Recursos concretos: el catálogo del modelo vigente y, por cada ejercicio cargado, su grafo y su schema de entrada
+pub fn list_resources(models: &ModelRegistry) -> Vec<Resource> {
+    let mut resources = vec![recurso(
+        URI_SUPUESTOS,
+        "supuestos",
+        "Catálogo de supuestos (letra, descripción, importe y requisitos) generado de la tabla de decisión vigente",
+    )];
+    for ejercicio in models.ejercicios() {
+        resources.push(recurso(
+            &format!("{}{}", PREFIJO_URI_MODELO, ejercicio),
+            &format!("modelo-{}", ejercicio),
+            &format!("Grafo de decisión GoRules del ejercicio {}", ejercicio),
+        ));
+        resources.push(recurso(
+            &format!("{}{}/esquema", PREFIJO_URI_MODELO, ejercicio),
+            &format!("esquema-{}", ejercicio),
+            &format!("Schema JSON de entrada del modelo del ejercicio {}", ejercicio),
+        ));
+    }
+    resources
+}
+
+/// Plantillas para leer cualquier ejercicio cargado
+pub fn list_resource_templates() -> Vec<ResourceTemplate> {
+    [
+        ("modelo", "{ejercicio}", "Grafo de decisión GoRules de un ejercicio"),
+        ("esquema", "{ejercicio}/esquema", "Schema JSON de entrada del modelo de un ejercicio"),
+    ]
+    .into_iter()
+    .map(|(name, path, description)| {
+        RawResourceTemplate {
+            uri_template: format!("{}{}", PREFIJO_URI_MODELO, path),
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            mime_type: Some(MIME_JSON.to_string()),
+        }
+        .no_annotation()
+    })
+    .collect()
+}
+
+/// Contenido de un recurso por su URI
+pub fn read_resource(models: &ModelRegistry, uri: &str) -> Result<ResourceContents, McpError> {
+    let no_encontrado = |message: String| McpError::resource_not_found(message, Some(serde_json::json!({ "uri": uri })));
+
+    let text = if uri == URI_SUPUESTOS {
+        let model = models
+            .current()
+            .ok_or_else(|| no_encontrado("No hay modelo de decisión aplicable al ejercicio actual".to_string()))?;
+        let catalogo = CatalogoSupuestos::from_model(&model).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        serde_json::to_string_pretty(&catalogo).map_err(|e| McpError::internal_error(e.to_string(), None))?
+    } else {
+        let ruta = uri
+            .strip_prefix(PREFIJO_URI_MODELO)
+            .ok_or_else(|| no_encontrado(format!("Recurso desconocido: {}", uri)))?;
+        let (ejercicio, esquema) = match ruta.strip_suffix("/esquema") {
+            Some(ejercicio) => (ejercicio, true),
+            None => (ruta, false),
+        };
+        let model = ejercicio
+            .parse::<i32>()
+            .ok()
+            .and_then(|ejercicio| models.get(ejercicio))
+            .ok_or_else(|| no_encontrado(format!("No hay modelo de decisión para el ejercicio '{}' (disponibles: {:?})", ejercicio, models.ejercicios())))?;
+        if esquema {
+            let schema = model
+                .input_schema
+                .as_ref()
+                .ok_or_else(|| no_encontrado(format!("El modelo del ejercicio {} no declara schema de entrada", model.ejercicio)))?;
+            serde_json::to_string_pretty(schema).map_err(|e| McpError::internal_error(e.to_string(), None))?
+        } else {
+            model.contenido.to_string()
+        }
+    };
+
+    Ok(ResourceContents::TextResourceContents {
+        uri: uri.to_string(),
+        mime_type: Some(MIME_JSON.to_string()),
+        text,
+    })
+}
+
+fn recurso(uri: &str, name: &str, description: &str) -> Resource {
+    RawResource {
+        description: Some(description.to_string()),
+        mime_type: Some(MIME_JSON.to_string()),
+        ..RawResource::new(uri, name)
+    }
+    .no_annotation()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogo_desde_tabla_de_supuestos() {
+        let catalogo = CatalogoSupuestos::from_model(&DecisionModel::embedded()).unwrap();
+
+        let letras: std::collections::BTreeSet<&str> = catalogo.supuestos.iter().map(|s| s.supuesto.as_str()).collect();
+        assert_eq!(letras.into_iter().collect::<Vec<_>>(), vec!["A", "B", "C", "D", "E"]);
+
+        let supuesto_a = catalogo.supuestos.iter().find(|s| s.regla == "regla-001").unwrap();
+        assert_eq!(supuesto_a.supuesto, "A");
+        assert_eq!(supuesto_a.importe_mensual, Some(725));
+        assert_eq!(supuesto_a.tiene_derecho_potencial, Some(true));
+        assert_eq!(supuesto_a.estado.as_deref(), Some("reconocido"));
+        assert!(supuesto_a.requisitos.contains_key("input.situacion"));
+    }
+
+    #[test]
+    fn test_read_resource() {
+        let models = ModelRegistry::embedded();
+        let uris: Vec<String> = list_resources(&models).into_iter().map(|r| r.raw.uri).collect();
+        assert_eq!(uris, vec!["bon://supuestos", "bon://modelo/2025", "bon://modelo/2025/esquema"]);
+
+        for uri in &uris {
+            let ResourceContents::TextResourceContents { text, .. } = read_resource(&models, uri).unwrap() else {
+                panic!("se esperaba un recurso de texto");
+            };
+            assert!(serde_json::from_str::<Value>(&text).is_ok());
+        }
+
+        let ResourceContents::TextResourceContents { text, .. } = read_resource(&models, "bon://modelo/2025").unwrap() else {
+            panic!("se esperaba un recurso de texto");
+        };
+        assert_eq!(text, crate::common::modelo::MODELO_EMBEBIDO);
+
+        assert!(read_resource(&models, "bon://modelo/1999").is_err());
+        assert!(read_resource(&models, "bon://otro").is_err());
+    }
+}
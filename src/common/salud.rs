@@ -0,0 +1,210 @@
+use axum::{Extension, Json, Router, extract::State, http::{StatusCode, header}, response::IntoResponse, routing::{get, post}};
+use serde::{Deserialize, Serialize};
+
+use super::autenticacion::{Identidad, Permiso, exigir};
+use super::metricas::{Metricas, TEXT_FORMAT};
+use super::modelo::{DecisionModel, ModelArgs, ModelRegistry, model_files};
+use super::recarga::reload_model;
+
+// =================== RUTAS DE SALUD Y MÉTRICAS ===================
+
+/// Rutas de salud para los servidores HTTP:
+/// - `/health`: el proceso responde (liveness)
+/// - `/ready`: hay un modelo de decisión cargado y compilado para el ejercicio vigente (readiness)
+/// - `/version`: versión del servidor y modelos de decisión cargados
+/// - `/metrics`: métricas de uso en el formato de texto de Prometheus
+pub fn router(models: ModelRegistry, metricas: Metricas) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .route("/version", get(version))
+        .with_state(models)
+        .merge(Router::new().route("/metrics", get(metrics)).with_state(metricas))
+}
+
+/// Rutas de administración. Deben montarse tras [`super::autenticacion::proteger`]:
+/// - `POST /admin/recargar`: vuelve a cargar los modelos de `decision_model_path` (permiso `recargar`)
+pub fn router_admin(models: ModelRegistry, model_args: ModelArgs) -> Router {
+    Router::new()
+        .route("/admin/recargar", post(recargar))
+        .with_state((models, model_args))
+}
+
+/// Modelo de decisión cargado, tal y como se informa en `/ready`, `/version` y `/admin/recargar`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModeloCargado {
+    pub ejercicio: i32,
+    pub version: String,
+    /// Fichero del modelo. Solo se informa en `/admin/recargar`, que exige autenticación:
+    /// las rutas públicas no revelan las rutas del servidor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origen: Option<String>,
+}
+
+impl From<&DecisionModel> for ModeloCargado {
+    fn from(model: &DecisionModel) -> Self {
+        Self {
+            ejercicio: model.ejercicio,
+            version: model.version.clone(),
+            origen: None,
+        }
+    }
+}
+
+/// Respuesta de `/health` y `/ready`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EstadoServicio {
+    pub estado: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub motivo: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modelos: Vec<ModeloCargado>,
+}
+
+/// Respuesta de `/version`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InfoVersion {
+    pub nombre: String,
+    pub version: String,
+    pub modelos: Vec<ModeloCargado>,
+}
+
+/// Respuesta de `/admin/recargar`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResultadoRecarga {
+    pub modelos: Vec<ModeloCargado>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errores: Vec<String>,
+}
+
+/// Modelos cargados en el registro, por ejercicio ascendente
+fn modelos_cargados(models: &ModelRegistry) -> Vec<ModeloCargado> {
+    models
+        .ejercicios()
+        .into_iter()
+        .filter_map(|ejercicio| models.get(ejercicio))
+        .map(|model| ModeloCargado::from(model.as_ref()))
+        .collect()
+}
+
+/// Modelos cargados con el fichero de origen de cada uno, para las respuestas autenticadas
+fn modelos_cargados_con_origen(models: &ModelRegistry) -> Vec<ModeloCargado> {
+    models
+        .ejercicios()
+        .into_iter()
+        .filter_map(|ejercicio| models.get(ejercicio))
+        .map(|model| ModeloCargado { origen: Some(model.origen.to_string()), ..ModeloCargado::from(model.as_ref()) })
+        .collect()
+}
+
+async fn health() -> Json<EstadoServicio> {
+    Json(EstadoServicio { estado: "ok".to_string(), motivo: None, modelos: Vec::new() })
+}
+
+async fn ready(State(models): State<ModelRegistry>) -> impl IntoResponse {
+    // El registro solo contiene modelos cuyo grafo ya se ha compilado y validado
+    if models.current().is_none() {
+        let motivo = match models.ejercicios().as_slice() {
+            [] => "No hay ningún modelo de decisión cargado".to_string(),
+            ejercicios => format!("No hay modelo de decisión aplicable al ejercicio actual (disponibles: {:?})", ejercicios),
+        };
+        let estado = EstadoServicio { estado: "no_listo".to_string(), motivo: Some(motivo), modelos: Vec::new() };
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(estado));
+    }
+    let estado = EstadoServicio { estado: "listo".to_string(), motivo: None, modelos: modelos_cargados(&models) };
+    (StatusCode::OK, Json(estado))
+}
+
+async fn version(State(models): State<ModelRegistry>) -> Json<InfoVersion> {
+    Json(InfoVersion {
+        nombre: env!("CARGO_PKG_NAME").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        modelos: modelos_cargados(&models),
+    })
+}
+
+async fn metrics(State(metricas): State<Metricas>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, TEXT_FORMAT)], metricas.texto())
+}
+
+async fn recargar(
+    State((models, model_args)): State<(ModelRegistry, ModelArgs)>,
+    Extension(identidad): Extension<Identidad>,
+) -> axum::response::Response {
+    if let Err(e) = exigir(&identidad, Permiso::Recargar) {
+        return e.into_response();
+    }
+    let Some(path) = model_args.decision_model_path.clone() else {
+        let resultado = ResultadoRecarga {
+            modelos: modelos_cargados_con_origen(&models),
+            errores: vec!["Se usa el modelo embebido: no hay ficheros de modelo que recargar".to_string()],
+        };
+        return (StatusCode::CONFLICT, Json(resultado)).into_response();
+    };
+
+    tracing::info!("Recarga de los modelos de decisión solicitada por '{}'", identidad.nombre);
+    let registry = models.clone();
+    let recarga = tokio::task::spawn_blocking(move || {
+        let mut errores = Vec::new();
+        let mut tools_changed = false;
+        match model_files(&path) {
+            Ok(files) => {
+                for file in files {
+                    match reload_model(&registry, &file, model_args.ejercicio_por_defecto()) {
+                        Ok(changed) => tools_changed |= changed,
+                        Err(e) => errores.push(e.to_string()),
+                    }
+                }
+            },
+            Err(e) => errores.push(e.to_string()),
+        }
+        if tools_changed {
+            registry.notify_tool_list_changed();
+        }
+        errores
+    }).await;
+
+    let errores = recarga.unwrap_or_else(|e| vec![format!("Error interno: {}", e)]);
+    for error in &errores {
+        tracing::error!("{}. Se mantiene el modelo de decisión anterior", error);
+    }
+    let status = if errores.is_empty() { StatusCode::OK } else { StatusCode::UNPROCESSABLE_ENTITY };
+    (status, Json(ResultadoRecarga { modelos: modelos_cargados_con_origen(&models), errores })).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ready_hasta_cargar_modelo() {
+        let models = ModelRegistry::new([]);
+        let response = ready(State(models.clone())).await.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        models.insert(std::sync::Arc::new(DecisionModel::embedded()));
+        let response = ready(State(models.clone())).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let Json(info) = version(State(models)).await;
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.modelos.len(), 1);
+        assert!(info.modelos[0].version.starts_with("sha256:"));
+        assert_eq!(info.modelos[0].origen, None);
+    }
+
+    #[tokio::test]
+    async fn test_recargar_exige_permiso() {
+        let models = ModelRegistry::new([DecisionModel::embedded()]);
+        let solo_evaluar = Identidad { nombre: "portal".to_string(), permisos: [Permiso::Evaluar].into() };
+
+        let response = recargar(State((models.clone(), ModelArgs::default())), Extension(solo_evaluar)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = recargar(State((models, ModelArgs::default())), Extension(Identidad::anonima())).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let resultado: ResultadoRecarga = serde_json::from_slice(&body).unwrap();
+        assert!(resultado.modelos[0].origen.is_some());
+    }
+}
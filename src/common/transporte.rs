@@ -0,0 +1,323 @@
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    Router,
+    http::{HeaderName, HeaderValue, Method},
+    serve::Listener,
+};
+use rustls_pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::{TlsAcceptor, rustls, server::TlsStream};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// Variable de entorno con los orígenes permitidos por CORS
+pub const CORS_ORIGINS_ENV: &str = "CORS_ORIGINS";
+
+/// Variable de entorno con los métodos permitidos por CORS
+pub const CORS_METHODS_ENV: &str = "CORS_METHODS";
+
+/// Variable de entorno con las cabeceras permitidas por CORS
+pub const CORS_HEADERS_ENV: &str = "CORS_HEADERS";
+
+/// Variable de entorno con la ruta del certificado TLS (PEM)
+pub const TLS_CERT_FILE_ENV: &str = "TLS_CERT_FILE";
+
+/// Variable de entorno con la ruta de la clave privada TLS (PEM)
+pub const TLS_KEY_FILE_ENV: &str = "TLS_KEY_FILE";
+
+/// Métodos permitidos por CORS si no se configuran otros
+pub const CORS_METHODS_POR_DEFECTO: &[&str] = &["GET", "POST", "DELETE", "OPTIONS"];
+
+/// Cabeceras de petición permitidas por CORS si no se configuran otras: las que envían los clientes MCP
+pub const CORS_HEADERS_POR_DEFECTO: &[&str] = &[
+    "content-type", "accept", "authorization", "x-api-key", "mcp-session-id", "mcp-protocol-version", "last-event-id",
+];
+
+/// Cabeceras de respuesta que un cliente MCP en el navegador necesita leer
+const CABECERAS_EXPUESTAS: &[&str] = &["mcp-session-id"];
+
+/// Tiempo máximo para completar el handshake TLS de una conexión
+const TIMEOUT_HANDSHAKE: Duration = Duration::from_secs(10);
+
+// =================== ERRORES ===================
+
+#[derive(Debug)]
+pub enum TransporteError {
+    Cors(String),
+    Lectura { path: PathBuf, message: String },
+    Tls(rustls::Error),
+    TlsIncompleto,
+}
+
+impl fmt::Display for TransporteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransporteError::Cors(message) => write!(f, "Configuración CORS no válida: {}", message),
+            TransporteError::Lectura { path, message } => {
+                write!(f, "No se puede leer el fichero PEM '{}': {}", path.display(), message)
+            },
+            TransporteError::Tls(source) => write!(f, "Configuración TLS no válida: {}", source),
+            TransporteError::TlsIncompleto => write!(f, "Para servir HTTPS hay que indicar el certificado y la clave TLS"),
+        }
+    }
+}
+
+impl std::error::Error for TransporteError {}
+
+// =================== ARGUMENTOS CLI ===================
+
+/// CORS y TLS de los servidores HTTP. Es la sección `[http]` de la configuración
+#[derive(Debug, Clone, Default, PartialEq, clap::Args, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpArgs {
+    /// Orígenes permitidos por CORS, separados por comas (p. ej. http://localhost:6274); `*` admite cualquiera. Sin orígenes no se responde a CORS
+    #[arg(long = "cors-origins", env = CORS_ORIGINS_ENV, value_name = "ORIGEN", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cors_origins: Option<Vec<String>>,
+
+    /// Métodos permitidos por CORS, separados por comas [por defecto: GET,POST,DELETE,OPTIONS]
+    #[arg(long = "cors-methods", env = CORS_METHODS_ENV, value_name = "METODO", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cors_methods: Option<Vec<String>>,
+
+    /// Cabeceras de petición permitidas por CORS, separadas por comas [por defecto: las que usan los clientes MCP]
+    #[arg(long = "cors-headers", env = CORS_HEADERS_ENV, value_name = "CABECERA", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cors_headers: Option<Vec<String>>,
+
+    /// Certificado TLS en PEM (con la cadena intermedia). Junto con --tls-key el servidor atiende HTTPS
+    #[arg(long = "tls-cert", env = TLS_CERT_FILE_ENV, value_name = "PATH")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// Clave privada TLS en PEM (PKCS#8, PKCS#1 o SEC1)
+    #[arg(long = "tls-key", env = TLS_KEY_FILE_ENV, value_name = "PATH")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_key: Option<PathBuf>,
+}
+
+impl HttpArgs {
+    /// Capa CORS según los argumentos, o `None` si no se ha indicado ningún origen
+    pub fn cors_layer(&self) -> Result<Option<CorsLayer>, TransporteError> {
+        let origins = match self.cors_origins.as_deref() {
+            None | Some([]) => return Ok(None),
+            Some(origins) if origins.iter().any(|origin| origin == "*") => AllowOrigin::any(),
+            Some(origins) => AllowOrigin::list(parse_all::<HeaderValue>(origins, "origen")?),
+        };
+        let por_defecto = |valores: &[&str]| valores.iter().map(|valor| valor.to_string()).collect::<Vec<_>>();
+        let methods = self.cors_methods.clone().unwrap_or_else(|| por_defecto(CORS_METHODS_POR_DEFECTO));
+        let headers = self.cors_headers.clone().unwrap_or_else(|| por_defecto(CORS_HEADERS_POR_DEFECTO));
+        let expuestas = CABECERAS_EXPUESTAS.iter().map(|cabecera| HeaderName::from_static(cabecera));
+        Ok(Some(
+            CorsLayer::new()
+                .allow_origin(origins)
+                .allow_methods(parse_all::<Method>(&methods, "método")?)
+                .allow_headers(parse_all::<HeaderName>(&headers, "cabecera")?)
+                .expose_headers(expuestas.collect::<Vec<_>>()),
+        ))
+    }
+
+    /// Añade la capa CORS al router si hay orígenes configurados. Debe aplicarse al router
+    /// completo para que las peticiones preflight se respondan antes de la autenticación
+    pub fn con_cors(&self, router: Router) -> Result<Router, TransporteError> {
+        Ok(match self.cors_layer()? {
+            Some(cors) => {
+                tracing::info!("CORS habilitado para los orígenes {:?}", self.cors_origins.as_deref().unwrap_or_default());
+                router.layer(cors)
+            },
+            None => router,
+        })
+    }
+
+    /// Aceptador TLS si se han indicado certificado y clave. Indicar solo uno de los dos es un error
+    pub fn tls_acceptor(&self) -> Result<Option<TlsAcceptor>, TransporteError> {
+        match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => tls_acceptor(cert, key).map(Some),
+            (None, None) => Ok(None),
+            _ => Err(TransporteError::TlsIncompleto),
+        }
+    }
+}
+
+/// Interpreta cada valor de una lista de configuración, indicando el primero que no es válido
+fn parse_all<T: std::str::FromStr>(valores: &[String], que: &str) -> Result<Vec<T>, TransporteError> {
+    valores
+        .iter()
+        .map(|valor| valor.trim())
+        .filter(|valor| !valor.is_empty())
+        .map(|valor| valor.parse().map_err(|_| TransporteError::Cors(format!("{} no válido: '{}'", que, valor))))
+        .collect()
+}
+
+// =================== TLS ===================
+
+/// Aceptador TLS con el certificado y la clave PEM indicados, usando el proveedor criptográfico `ring`
+pub fn tls_acceptor(cert: &Path, key: &Path) -> Result<TlsAcceptor, TransporteError> {
+    let lectura = |path: &Path, e: &dyn fmt::Display| TransporteError::Lectura { path: path.to_path_buf(), message: e.to_string() };
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| lectura(cert, &e))?;
+    if certs.is_empty() {
+        return Err(lectura(cert, &"no contiene ningún certificado"));
+    }
+    let key_der = PrivateKeyDer::from_pem_file(key).map_err(|e| lectura(key, &e))?;
+
+    let config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key_der))
+        .map_err(TransporteError::Tls)?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Listener de axum que entrega conexiones ya cifradas. Los handshakes se hacen en
+/// tareas aparte para que un cliente lento no bloquee la aceptación de los demás
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    conexiones: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, acceptor: TlsAcceptor) -> std::io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (tx, conexiones) = mpsc::channel(64);
+        tokio::spawn(async move {
+            while !tx.is_closed() {
+                let (stream, remote) = match listener.accept().await {
+                    Ok(conexion) => conexion,
+                    Err(e) => {
+                        tracing::warn!("Error al aceptar una conexión TCP: {}", e);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        continue;
+                    },
+                };
+                let (acceptor, tx) = (acceptor.clone(), tx.clone());
+                tokio::spawn(async move {
+                    match tokio::time::timeout(TIMEOUT_HANDSHAKE, acceptor.accept(stream)).await {
+                        Ok(Ok(tls)) => {
+                            let _ = tx.send((tls, remote)).await;
+                        },
+                        Ok(Err(e)) => tracing::debug!("Handshake TLS fallido con {}: {}", remote, e),
+                        Err(_) => tracing::debug!("Handshake TLS con {} sin completar en {:?}", remote, TIMEOUT_HANDSHAKE),
+                    }
+                });
+            }
+        });
+        Ok(Self { local_addr, conexiones })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.conexiones.recv().await {
+            Some(conexion) => conexion,
+            // La tarea de aceptación solo termina si se suelta el listener
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+// =================== SERVIDOR ===================
+
+/// Sirve el router por HTTP o, si hay aceptador TLS, por HTTPS hasta que termine `shutdown`
+pub async fn serve(
+    listener: TcpListener,
+    router: Router,
+    tls: Option<TlsAcceptor>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    match tls {
+        Some(acceptor) => {
+            axum::serve(TlsListener::new(listener, acceptor)?, router)
+                .with_graceful_shutdown(shutdown)
+                .await
+        },
+        None => axum::serve(listener, router).with_graceful_shutdown(shutdown).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use clap::Parser;
+
+    #[derive(Debug, Parser)]
+    struct Args {
+        #[command(flatten)]
+        http: HttpArgs,
+    }
+
+    /// Arranca `router` en un puerto libre y devuelve su dirección
+    async fn arrancar(router: Router, tls: Option<TlsAcceptor>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, router, tls, std::future::pending()));
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_cors_responde_preflight() {
+        let args = Args::parse_from(["test", "--cors-origins", "http://localhost:6274"]);
+        let router = args.http.con_cors(Router::new().route("/mcp", get(|| async { "ok" }))).unwrap();
+        let addr = arrancar(router, None).await;
+
+        let respuesta = reqwest::Client::new()
+            .request(Method::OPTIONS, format!("http://{}/mcp", addr))
+            .header("origin", "http://localhost:6274")
+            .header("access-control-request-method", "POST")
+            .header("access-control-request-headers", "authorization,mcp-session-id")
+            .send()
+            .await
+            .unwrap();
+        assert!(respuesta.status().is_success());
+        assert_eq!(respuesta.headers()["access-control-allow-origin"], "http://localhost:6274");
+        let cabeceras = respuesta.headers()["access-control-allow-headers"].to_str().unwrap();
+        assert!(cabeceras.contains("mcp-session-id"));
+
+        let args = Args::parse_from(["test", "--cors-origins", "http://localhost:6274", "--cors-methods", "GET,NO VÁLIDO"]);
+        assert!(matches!(args.http.cors_layer(), Err(TransporteError::Cors(_))));
+        assert!(Args::parse_from(["test"]).http.cors_layer().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tls_con_certificado_generado() {
+        let dir = tempfile::tempdir().unwrap();
+        let certificado = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let (cert, key) = (dir.path().join("cert.pem"), dir.path().join("key.pem"));
+        std::fs::write(&cert, certificado.cert.pem()).unwrap();
+        std::fs::write(&key, certificado.key_pair.serialize_pem()).unwrap();
+
+        let args = Args::parse_from(["test", "--tls-cert", cert.to_str().unwrap(), "--tls-key", key.to_str().unwrap()]);
+        let acceptor = args.http.tls_acceptor().unwrap();
+        assert!(acceptor.is_some());
+        let addr = arrancar(Router::new().route("/health", get(|| async { "ok" })), acceptor).await;
+
+        let cliente = reqwest::Client::builder()
+            .add_root_certificate(reqwest::Certificate::from_pem(certificado.cert.pem().as_bytes()).unwrap())
+            .resolve("localhost", addr)
+            .build()
+            .unwrap();
+        let respuesta = cliente.get(format!("https://localhost:{}/health", addr.port())).send().await.unwrap();
+        assert_eq!(respuesta.text().await.unwrap(), "ok");
+
+        // Sin TLS en el cliente la conexión no llega al router
+        assert!(reqwest::get(format!("http://{}/health", addr)).await.is_err());
+
+        assert!(matches!(tls_acceptor(&key, &key), Err(TransporteError::Lectura { .. })));
+        let solo_cert = Args::parse_from(["test", "--tls-cert", cert.to_str().unwrap()]);
+        assert!(matches!(solo_cert.http.tls_acceptor(), Err(TransporteError::TlsIncompleto)));
+    }
+}
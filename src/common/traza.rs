@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use zen_engine::DecisionGraphTrace;
+
+use rmcp::schemars;
+
+/// Nodo del modelo que decide el supuesto aplicable
+pub const NODO_SUPUESTOS: &str = "decision-table-001";
+
+/// Nodo del modelo que comprueba el parentesco (primer grado)
+pub const NODO_PARENTESCO: &str = "eval_parentesco";
+
+// =================== TRAZA DE EVALUACIÓN ===================
+
+/// Regla de una tabla de decisión que ha coincidido con los datos de entrada
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ReglaAplicada {
+    #[schemars(description = "Identificador de la regla (`_id`) en la tabla de decisión")]
+    pub id: String,
+
+    #[schemars(description = "Descripción de la regla (`_description`), vacía si la regla no la tiene")]
+    pub descripcion: String,
+
+    #[schemars(description = "Condiciones de la regla por campo de entrada, tal y como están escritas en la tabla (p. ej. situacion: '\"parto\",\"nacimiento\"'). Los campos sin condición se omiten")]
+    pub condiciones: BTreeMap<String, String>,
+
+    #[schemars(description = "Valores de entrada con los que se ha comparado la regla")]
+    pub valores: BTreeMap<String, serde_json::Value>,
+}
+
+/// Nodo del grafo evaluado, en orden de ejecución
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct NodoEvaluado {
+    #[schemars(description = "Identificador del nodo en el modelo de decisión")]
+    pub id: String,
+
+    #[schemars(description = "Nombre del nodo en el modelo de decisión")]
+    pub nombre: String,
+
+    #[schemars(description = "Posición en el orden de evaluación, empezando en 0")]
+    pub orden: u32,
+
+    #[schemars(description = "Tiempo de evaluación del nodo, p. ej. '329.2µs'")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duracion: Option<String>,
+
+    #[schemars(description = "Regla que ha coincidido, si el nodo es una tabla de decisión")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regla: Option<ReglaAplicada>,
+}
+
+/// Explicación de cómo el modelo de decisión ha llegado al resultado
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct TrazaEvaluacion {
+    #[schemars(description = "Regla de la tabla de supuestos que determina el resultado. Las reglas se evalúan en orden y se aplica la primera que coincide")]
+    pub regla_aplicada: Option<ReglaAplicada>,
+
+    #[schemars(description = "Resultado de la comprobación de parentesco de primer grado")]
+    pub parentesco_valido: Option<bool>,
+
+    #[schemars(description = "Nodos evaluados en orden, con su duración")]
+    pub nodos: Vec<NodoEvaluado>,
+
+    #[schemars(description = "Tiempo total de evaluación del grafo")]
+    pub duracion_total: String,
+}
+
+impl TrazaEvaluacion {
+    /// Construye la explicación a partir de la traza que devuelve zen-engine
+    pub fn from_trace<'a>(trace: impl IntoIterator<Item = &'a DecisionGraphTrace>, duracion_total: String) -> Self {
+        let trace: Vec<&DecisionGraphTrace> = trace.into_iter().collect();
+        let mut nodos: Vec<NodoEvaluado> = trace.iter().copied().map(NodoEvaluado::from).collect();
+        nodos.sort_by_key(|nodo| nodo.orden);
+
+        let regla_aplicada = nodos
+            .iter()
+            .find(|nodo| nodo.id == NODO_SUPUESTOS)
+            .and_then(|nodo| nodo.regla.clone());
+
+        let parentesco_valido = trace
+            .iter()
+            .find(|nodo| nodo.name == NODO_PARENTESCO)
+            .and_then(|nodo| serde_json::Value::from(nodo.output.clone()).get("parentesco_valido")?.as_bool());
+
+        Self {
+            regla_aplicada,
+            parentesco_valido,
+            nodos,
+            duracion_total,
+        }
+    }
+}
+
+impl From<&DecisionGraphTrace> for NodoEvaluado {
+    fn from(trace: &DecisionGraphTrace) -> Self {
+        Self {
+            id: trace.id.clone(),
+            nombre: trace.name.clone(),
+            orden: trace.order,
+            duracion: trace.performance.clone(),
+            regla: trace.trace_data.as_ref().and_then(ReglaAplicada::from_trace_data),
+        }
+    }
+}
+
+impl ReglaAplicada {
+    /// Interpreta el `traceData` de un nodo de tabla de decisión:
+    /// `{"rule": {"_id", "_description", "<campo>[<columna>]": "<condición>"}, "reference_map": {...}}`
+    pub fn from_trace_data(trace_data: &serde_json::Value) -> Option<Self> {
+        let rule = trace_data.get("rule")?.as_object()?;
+        let texto = |clave: &str| rule.get(clave).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+
+        let condiciones = rule
+            .iter()
+            .filter(|(clave, _)| !clave.starts_with('_'))
+            .filter_map(|(clave, condicion)| {
+                let condicion = condicion.as_str().filter(|c| !c.is_empty())?;
+                let campo = clave.split_once('[').map_or(clave.as_str(), |(campo, _)| campo);
+                Some((campo.to_string(), condicion.to_string()))
+            })
+            .collect();
+
+        let valores = trace_data
+            .get("reference_map")
+            .and_then(|valores| valores.as_object())
+            .map(|valores| valores.iter().map(|(campo, valor)| (campo.clone(), valor.clone())).collect())
+            .unwrap_or_default();
+
+        Some(Self {
+            id: texto("_id"),
+            descripcion: texto("_description"),
+            condiciones,
+            valores,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_regla_desde_trace_data() {
+        let trace_data = json!({
+            "index": 0,
+            "reference_map": {"input.situacion": "parto", "parentesco_valido": true},
+            "rule": {
+                "_description": "Supuesto E: Familia monoparental cualquier hijo",
+                "_id": "regla-005",
+                "input.numero_hijos[f3560ff1]": "",
+                "input.situacion[89ca6fb3]": "\"parto\",\"nacimiento\"",
+                "parentesco_valido[input-parentesco]": "true"
+            }
+        });
+
+        let regla = ReglaAplicada::from_trace_data(&trace_data).unwrap();
+        assert_eq!(regla.id, "regla-005");
+        assert_eq!(regla.descripcion, "Supuesto E: Familia monoparental cualquier hijo");
+        assert_eq!(regla.condiciones.len(), 2);
+        assert_eq!(regla.condiciones["input.situacion"], "\"parto\",\"nacimiento\"");
+        assert_eq!(regla.valores["parentesco_valido"], json!(true));
+
+        assert!(ReglaAplicada::from_trace_data(&json!(null)).is_none());
+    }
+}
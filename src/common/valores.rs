@@ -0,0 +1,182 @@
+use serde::{Deserialize, Deserializer, Serialize, de::Error as DeError};
+use std::fmt;
+use std::str::FromStr;
+
+use rmcp::schemars;
+
+// =================== NORMALIZACIÓN ===================
+
+/// Normaliza un valor libre para compararlo con los valores canónicos:
+/// sin espacios extremos, en minúsculas, sin tildes y con `_` como separador
+fn normalizar(valor: &str) -> String {
+    valor
+        .trim()
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'á' | 'à' | 'ä' | 'â' => 'a',
+            'é' | 'è' | 'ë' | 'ê' => 'e',
+            'í' | 'ì' | 'ï' | 'î' => 'i',
+            'ó' | 'ò' | 'ö' | 'ô' => 'o',
+            'ú' | 'ù' | 'ü' | 'û' => 'u',
+            'ñ' => 'n',
+            ' ' | '-' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Error al interpretar un valor que no corresponde a ningún valor canónico ni alias
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValorNoValido {
+    pub campo: &'static str,
+    pub valor: String,
+    pub permitidos: &'static [&'static str],
+}
+
+impl fmt::Display for ValorNoValido {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "valor de {} no válido: '{}'. Valores permitidos: {}",
+            self.campo,
+            self.valor,
+            self.permitidos.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ValorNoValido {}
+
+/// Enum de valores canónicos con sus alias. Se serializa y documenta (schemars) con el
+/// valor canónico y se deserializa normalizando mayúsculas, tildes y sinónimos
+macro_rules! valores_normalizados {
+    (
+        $(#[$meta:meta])*
+        $nombre:ident, $campo:literal {
+            $($variante:ident => $canonico:literal $(| $alias:literal)*,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, schemars::JsonSchema)]
+        #[schemars(inline)]
+        pub enum $nombre {
+            $(#[serde(rename = $canonico)] $variante,)+
+        }
+
+        impl $nombre {
+            /// Valores canónicos, en el mismo orden que el schema de entrada del modelo
+            pub const VALORES: &'static [&'static str] = &[$($canonico),+];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($nombre::$variante => $canonico,)+
+                }
+            }
+        }
+
+        impl FromStr for $nombre {
+            type Err = ValorNoValido;
+
+            fn from_str(valor: &str) -> Result<Self, Self::Err> {
+                match normalizar(valor).as_str() {
+                    $($canonico $(| $alias)* => Ok($nombre::$variante),)+
+                    _ => Err(ValorNoValido {
+                        campo: $campo,
+                        valor: valor.to_string(),
+                        permitidos: Self::VALORES,
+                    }),
+                }
+            }
+        }
+
+        impl fmt::Display for $nombre {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $nombre {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let valor = String::deserialize(deserializer)?;
+                valor.parse().map_err(DeError::custom)
+            }
+        }
+    };
+}
+
+// =================== PARENTESCO Y SITUACIÓN ===================
+
+valores_normalizados! {
+    /// Relación familiar con la persona que necesita cuidado
+    Parentesco, "parentesco" {
+        Padre => "padre" | "papa" | "progenitor",
+        Madre => "madre" | "mama" | "progenitora",
+        Hijo => "hijo",
+        Hija => "hija",
+        Conyuge => "conyuge",
+        Pareja => "pareja" | "pareja_de_hecho" | "companero" | "companera",
+        Esposo => "esposo",
+        Esposa => "esposa",
+        Mujer => "mujer",
+        Marido => "marido",
+    }
+}
+
+valores_normalizados! {
+    /// Situación que motiva la necesidad de cuidado. `nacimiento` y `nacimiento_multiple`,
+    /// que la tabla de decisión trata igual que el parto, se normalizan a `parto` y `parto_multiple`
+    Situacion, "situacion" {
+        Parto => "parto" | "nacimiento",
+        Adopcion => "adopcion",
+        Acogimiento => "acogimiento" | "acogida",
+        PartoMultiple => "parto_multiple" | "nacimiento_multiple",
+        AdopcionMultiple => "adopcion_multiple",
+        AcogimientoMultiple => "acogimiento_multiple" | "acogida_multiple",
+        Enfermedad => "enfermedad",
+        Accidente => "accidente",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normaliza_mayusculas_tildes_y_sinonimos() {
+        assert_eq!("Madre".parse::<Parentesco>(), Ok(Parentesco::Madre));
+        assert_eq!(" CÓNYUGE ".parse::<Parentesco>(), Ok(Parentesco::Conyuge));
+        assert_eq!("pareja de hecho".parse::<Parentesco>(), Ok(Parentesco::Pareja));
+        assert_eq!("Adopción".parse::<Situacion>(), Ok(Situacion::Adopcion));
+        assert_eq!("nacimiento".parse::<Situacion>(), Ok(Situacion::Parto));
+        assert_eq!("Nacimiento múltiple".parse::<Situacion>(), Ok(Situacion::PartoMultiple));
+    }
+
+    #[test]
+    fn test_rechaza_valores_desconocidos() {
+        let error = "hermano".parse::<Parentesco>().unwrap_err();
+        assert_eq!(error.valor, "hermano");
+        assert!(error.to_string().contains("padre, madre"));
+
+        let error = serde_json::from_value::<Situacion>(json!("mudanza")).unwrap_err();
+        assert!(error.to_string().contains("situacion"));
+    }
+
+    #[test]
+    fn test_serializa_valor_canonico() {
+        assert_eq!(serde_json::to_value(Situacion::AcogimientoMultiple).unwrap(), json!("acogimiento_multiple"));
+        let situacion: Situacion = serde_json::from_value(json!("Acogida múltiple")).unwrap();
+        assert_eq!(serde_json::to_value(situacion).unwrap(), json!("acogimiento_multiple"));
+    }
+
+    #[test]
+    fn test_schema_enum_canonico() {
+        let schema = serde_json::to_value(schemars::schema_for!(Parentesco)).unwrap();
+        let valores: Vec<&str> = schema["enum"].as_array().unwrap().iter().map(|v| v.as_str().unwrap()).collect();
+        assert_eq!(valores, Parentesco::VALORES);
+    }
+}
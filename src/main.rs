@@ -0,0 +1,177 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use rmcp::{
+    ServiceExt,
+    transport::{
+        sse_server::{SseServer, SseServerConfig},
+        stdio,
+        streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager},
+    },
+};
+use tokio_util::sync::CancellationToken;
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+mod common;
+use common::autenticacion;
+use common::calculadora::Calculadora;
+use common::configuracion::{ConfigArgs, Configuracion};
+use common::metricas::Metricas;
+use common::modelo::ModelRegistry;
+use common::salud;
+use common::transporte;
+
+/// Calculadora de ayudas para excedencia como servidor MCP
+#[derive(Debug, Parser)]
+#[command(name = "bon-calculadora", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    comando: Comando,
+}
+
+#[derive(Debug, Subcommand)]
+enum Comando {
+    /// Sirve la calculadora por uno o varios transportes MCP
+    Serve(ServeArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct ServeArgs {
+    /// Transportes MCP separados por comas. `sse` y `http` pueden servirse juntos en el mismo puerto; `stdio` va solo
+    #[arg(long = "transport", value_enum, value_delimiter = ',', default_value = "stdio")]
+    transportes: Vec<Transporte>,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Transporte {
+    /// Entrada y salida estándar, para clientes locales como Claude Desktop
+    Stdio,
+    /// SSE en `/sse` y `/message`
+    Sse,
+    /// Streamable HTTP en `/mcp`
+    Http,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let Comando::Serve(args) = Cli::parse().comando;
+    let config = args.config.load()?;
+    if args.config.print_config {
+        print!("{}", config.to_toml()?);
+        return Ok(());
+    }
+
+    if args.transportes.contains(&Transporte::Stdio) {
+        if args.transportes.len() > 1 {
+            anyhow::bail!("El transporte stdio no se puede combinar con sse ni http");
+        }
+        serve_stdio(config).await
+    } else {
+        serve_http(config, &args.transportes).await
+    }
+}
+
+async fn serve_stdio(config: Configuracion) -> anyhow::Result<()> {
+    // stdout es el canal MCP: el log va a stderr y sin colores
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(config.servidor.log()))
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .init();
+
+    let models = ModelRegistry::new([]);
+    config.model.load_models(&models)?;
+    let decisiones = config.model.load_decisiones(&Calculadora::tool_names())?;
+
+    tracing::info!("Starting Calculator MCP server using stdio transport");
+
+    let metricas = Metricas::new();
+    let calculadora = Calculadora::new(models).with_metricas(metricas.clone()).with_decisiones(decisiones);
+    let service = calculadora.serve(stdio()).await.inspect_err(|e| {
+        tracing::error!("serving error: {:?}", e);
+    })?;
+
+    service.waiting().await?;
+
+    // Sin endpoint HTTP, las métricas de la sesión se vuelcan al log al terminar
+    tracing::info!("Métricas al terminar:\n{}", metricas.texto());
+    Ok(())
+}
+
+async fn serve_http(config: Configuracion, transportes: &[Transporte]) -> anyhow::Result<()> {
+    tracing_subscriber::registry()
+        .with(EnvFilter::new(config.servidor.log()))
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    let autenticacion = config.auth.load()?;
+    let tls = config.http.tls_acceptor()?;
+    let decisiones = config.model.load_decisiones(&Calculadora::tool_names())?;
+    // Los modelos se cargan con el servidor ya escuchando: `/ready` responde 503 hasta tenerlos
+    let models = ModelRegistry::new([]);
+    let metricas = Metricas::new();
+    let ct = CancellationToken::new();
+
+    let listener = tokio::net::TcpListener::bind(config.servidor.bind_address()).await?;
+    let bind = listener.local_addr()?;
+    tracing::info!(
+        "Starting Calculator MCP server ({:?}) on {}://{}",
+        transportes,
+        if tls.is_some() { "https" } else { "http" },
+        bind
+    );
+
+    let mut protegido = salud::router_admin(models.clone(), config.model.clone());
+    let mut sse_server = None;
+    if transportes.contains(&Transporte::Sse) {
+        let (server, router) = SseServer::new(SseServerConfig {
+            bind,
+            sse_path: config.sse.sse_path(),
+            post_path: config.sse.post_path(),
+            ct: ct.child_token(),
+            sse_keep_alive: Some(config.sse.keep_alive()),
+        });
+        protegido = protegido.merge(router);
+        sse_server = Some(server);
+    }
+    if transportes.contains(&Transporte::Http) {
+        let (session_models, session_metricas, session_decisiones) = (models.clone(), metricas.clone(), decisiones.clone());
+        let service = StreamableHttpService::new(
+            move || Ok(Calculadora::new(session_models.clone())
+                .with_metricas(session_metricas.clone())
+                .with_decisiones(session_decisiones.clone())),
+            LocalSessionManager::default().into(),
+            StreamableHttpServerConfig { sse_keep_alive: Some(config.sse.keep_alive()), ..Default::default() },
+        );
+        protegido = protegido.nest_service("/mcp", service);
+    }
+
+    let router = autenticacion::proteger(protegido, autenticacion)
+        .merge(salud::router(models.clone(), metricas.clone()));
+    let router = config.http.con_cors(router)?;
+
+    let server_ct = ct.clone();
+    let server = tokio::spawn(transporte::serve(listener, router, tls, async move {
+        server_ct.cancelled().await;
+        tracing::info!("Calculator MCP server cancelled");
+    }));
+
+    let model_args = config.model.clone();
+    let loaded_models = models.clone();
+    tokio::task::spawn_blocking(move || model_args.load_models(&loaded_models)).await??;
+
+    // Las sesiones SSE se atienden cuando ya hay modelos cargados
+    if let Some(sse_server) = sse_server {
+        sse_server.with_service(move || {
+            Calculadora::new(models.clone()).with_metricas(metricas.clone()).with_decisiones(decisiones.clone())
+        });
+    }
+
+    tokio::signal::ctrl_c().await?;
+    ct.cancel();
+    if let Err(e) = server.await? {
+        tracing::error!(error = %e, "Calculator MCP server shutdown with error");
+    }
+    Ok(())
+}
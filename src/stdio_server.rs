@@ -1,27 +0,0 @@
-use anyhow::Result;
-
-use rmcp::{ServiceExt, transport::stdio};
-use tracing_subscriber::{self, EnvFilter};
-
-mod common;
-use common::calculadora::Calculadora;
-
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize the tracing subscriber with file and stdout logging
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
-        .with_writer(std::io::stderr)
-        .with_ansi(false)
-        .init();
-
-    tracing::info!("Starting Calculator MCP server using stdio transport");
-
-    // Create an instance of our calculator router
-    let service = Calculadora::new().serve(stdio()).await.inspect_err(|e| {
-        tracing::error!("serving error: {:?}", e);
-    })?;
-
-    service.waiting().await?;
-    Ok(())
-}
\ No newline at end of file